@@ -1,4 +1,5 @@
 pub mod movement;
+pub mod split;
 pub mod zoom;
 use crate::config::CameraSettings;
 use crate::utilities::easing::{EasingFunction, EasingType, TimeEase};
@@ -7,6 +8,7 @@ use movement::{
     follow::{FollowMarker, Target},
     {CameraMovementPlugin, MovementMode},
 };
+use split::SplitScreenPlugin;
 use zoom::ZoomPlugin;
 
 /// Plugin that handles everything related to managing the camera
@@ -23,12 +25,14 @@ impl Plugin for CameraPlugin {
                 ..default()
             },
             CameraMarker,
+            // The menus and the HUD of the first player are shown by this camera, even while a split screen camera is added
+            IsDefaultUiCamera,
             TimeEase::new(0, 1000, 0., 1., EasingFunction::Sine, EasingType::Out),
-            FollowMarker::new(Target::Player),
+            FollowMarker::all(Target::players()),
             MovementMode::Follow,
         );
-        app.add_plugins((ZoomPlugin, CameraMovementPlugin))
-            .insert_resource(CameraSpeed(5.))
+        app.add_plugins((ZoomPlugin, CameraMovementPlugin, SplitScreenPlugin))
+            .insert_resource(CameraSpeed(300.))
             .add_systems(Startup, setup)
             .world_mut()
             .spawn(bundle);
@@ -51,6 +55,6 @@ fn setup(
 #[derive(Component)]
 pub struct CameraMarker;
 
-/// Defines the cameras speed
+/// Defines the cameras speed in pixels per second
 #[derive(Resource)]
 pub struct CameraSpeed(pub f32);
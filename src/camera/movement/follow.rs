@@ -1,14 +1,50 @@
-use crate::camera::CameraMarker;
-use crate::utilities::easing::TimeEase;
-use bevy::{prelude::*, window::PrimaryWindow};
-use std::collections::HashMap;
+use crate::config::CameraSettings;
+use crate::player::MAX_PLAYERS;
+use bevy::prelude::*;
+use bevy_rapier2d::dynamics::Velocity;
 
+/// Component to mark an entity that follows its targets, keeping the center of all of them in view
 #[derive(Component)]
-pub struct FollowMarker(Target);
+pub struct FollowMarker {
+    targets: Vec<Target>,
+    /// The center of the dead zone, which the targets can move around without moving the follower
+    anchor: Option<Vec2>,
+    /// The velocity of the spring that pulls the follower along
+    velocity: Vec2,
+}
 
 impl FollowMarker {
     pub fn new(target: Target) -> Self {
-        FollowMarker(target)
+        FollowMarker::all([target])
+    }
+
+    /// Creates a marker that follows every one of the given targets at once
+    pub fn all(targets: impl IntoIterator<Item = Target>) -> Self {
+        FollowMarker {
+            targets: targets.into_iter().collect(),
+            anchor: None,
+            velocity: Vec2::ZERO,
+        }
+    }
+
+    /// Returns whether the entity with the given marker is one of the followed targets
+    pub fn follows(&self, marker: &TargetMarker) -> bool {
+        self.targets.contains(&marker.0)
+    }
+
+    /// Returns the lowest and highest corner of the box around the followed targets, if any of them exist
+    pub fn span<'a>(
+        &self,
+        targets: impl IntoIterator<Item = (&'a TargetMarker, &'a Transform)>,
+    ) -> Option<(Vec3, Vec3)> {
+        targets
+            .into_iter()
+            .filter(|(marker, _)| self.follows(marker))
+            .map(|(_, transform)| transform.translation)
+            .fold(None, |span, position| match span {
+                Some((min, max)) => Some((min.min(position), max.max(position))),
+                None => Some((position, position)),
+            })
     }
 }
 
@@ -24,35 +60,87 @@ impl TargetMarker {
 
 #[derive(Eq, Hash, PartialEq, Clone, Copy)]
 pub enum Target {
-    Player,
+    /// The player with the given [PlayerNumber](crate::player::PlayerNumber)
+    Player(usize),
 }
 
-/// System that pulls the following entity towards its target. Speed depends on delta transform
+impl Target {
+    /// Returns the targets of every player that can take part in the game
+    pub fn players() -> impl Iterator<Item = Target> {
+        (0..MAX_PLAYERS).map(Target::Player)
+    }
+}
+
+/// System that pulls the following entity towards the center of its targets with a critically damped spring
+///
+/// The targets can move around a dead zone without moving the follower, and it looks ahead in the direction they move.
+/// The spring is solved exactly for every frame instead of being stepped, so it follows the same way at any frame rate
 pub fn following_movement_system(
-    mut follower: Query<(&FollowMarker, &mut Transform), Without<TargetMarker>>,
-    target: Query<(&TargetMarker, &Transform), Without<FollowMarker>>,
+    camera_settings: Res<CameraSettings>,
+    mut follower: Query<(&mut FollowMarker, &mut Transform), Without<TargetMarker>>,
+    target: Query<(&TargetMarker, &Transform, Option<&Velocity>), Without<FollowMarker>>,
     time: Res<Time>,
-    window: Query<&Window, With<PrimaryWindow>>,
-    time_ease: Query<&TimeEase, With<CameraMarker>>,
 ) {
-    let width = window.single().width() / time_ease.single().get_end_val();
-    let height = window.single().height() / time_ease.single().get_end_val();
-    let mut map = HashMap::new();
-    target.iter().for_each(|(marker, transform)| {
-        map.insert(marker.0, transform);
-    });
-    follower.iter_mut().for_each(|(marker, mut transform)| {
-        let target_transform = **map.get(&marker.0).unwrap();
-        let delta = target_transform.translation - transform.translation;
-        transform.translation += delta * time.delta_seconds();
-        if delta.x.abs() > 0.3 * width {
-            transform.translation.x += (delta.x.abs() - 0.3 * width) * delta.x.abs() / delta.x;
-        }
-        if delta.y.abs() > 0.3 * height {
-            transform.translation.y += (delta.y.abs() - 0.3 * height) * delta.y.abs() / delta.y;
-        }
-        if transform.translation.x == f32::INFINITY || transform.translation.y == f32::INFINITY {
-            transform.translation = target_transform.translation;
-        }
+    let half_dead_zone = camera_settings.follow_dead_zone / 2.;
+    let frequency = camera_settings.follow_stiffness.sqrt();
+    follower.iter_mut().for_each(|(mut marker, mut transform)| {
+        let targets = target
+            .iter()
+            .map(|(marker, transform, _)| (marker, transform));
+        let Some((min, max)) = marker.span(targets) else {
+            return;
+        };
+        let center = ((min + max) / 2.).truncate();
+        // The dead zone is dragged along by the targets once they reach its edge
+        let anchor = marker
+            .anchor
+            .unwrap_or(center)
+            .clamp(center - half_dead_zone, center + half_dead_zone);
+        let (velocity_sum, count) = target
+            .iter()
+            .filter(|(target_marker, ..)| marker.follows(target_marker))
+            .filter_map(|(.., velocity)| velocity)
+            .fold((Vec2::ZERO, 0), |(sum, count), velocity| {
+                (sum + velocity.linvel, count + 1)
+            });
+        let velocity = match count {
+            0 => Vec2::ZERO,
+            _ => velocity_sum / count as f32,
+        };
+        let look_ahead = (velocity * camera_settings.look_ahead_time).clamp(
+            -camera_settings.max_look_ahead,
+            camera_settings.max_look_ahead,
+        );
+        let (position, spring_velocity) = critically_damped_spring(
+            transform.translation.truncate(),
+            marker.velocity,
+            anchor + look_ahead,
+            frequency,
+            time.delta_seconds(),
+        );
+        marker.anchor = Some(anchor);
+        marker.velocity = spring_velocity;
+        transform.translation = position.extend(transform.translation.z);
     });
 }
+
+/// Helper function that advances a critically damped spring with the given angular frequency by the given number of seconds,
+/// returning its new position and velocity
+///
+/// It uses the exact solution of the spring, so it can't overshoot or blow up,
+/// and while the goal stays in place any number of short steps ends up where one long step does
+fn critically_damped_spring(
+    position: Vec2,
+    velocity: Vec2,
+    goal: Vec2,
+    frequency: f32,
+    delta: f32,
+) -> (Vec2, Vec2) {
+    let offset = position - goal;
+    let decay = (-frequency * delta).exp();
+    let change = (velocity + frequency * offset) * delta;
+    (
+        goal + (offset + change) * decay,
+        (velocity - frequency * change) * decay,
+    )
+}
@@ -1,25 +1,146 @@
-use bevy::prelude::*;
+use bevy::{prelude::*, window::PrimaryWindow};
 
 use crate::{
-    camera::{CameraMarker, CameraSpeed},
-    input::PlayerInput,
-    states::AppState,
+    camera::{zoom::ZoomAnchor, CameraMarker, CameraSpeed},
+    config::{CameraSettings, LevelSettings},
+    input::{ctrl_held, PlayerInput},
+    level_management::Level,
+    states::{editing::cursor_not_over_ui, AppState},
+    utilities::easing::{EasingFunction, EasingType, TimeEase},
 };
 
 pub struct InputMovementPlugin;
 
 impl Plugin for InputMovementPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, handle_input.run_if(in_state(AppState::Editing)));
+        app.add_systems(
+            Update,
+            (
+                handle_input,
+                zoom_to_cursor
+                    .run_if(not(ctrl_held))
+                    .run_if(cursor_not_over_ui),
+                pan_with_mouse,
+                zoom_to_fit,
+            )
+                .run_if(in_state(AppState::Editing)),
+        );
     }
 }
 
+/// How much of the window the level covers after zooming to fit
+const FIT_MARGIN: f32 = 0.9;
+
 /// This System moves the camera according to user input
 fn handle_input(
+    mut commands: Commands,
     camera_speed: Res<CameraSpeed>,
     player_input: Res<PlayerInput>,
-    mut camera_transform: Query<&mut Transform, With<CameraMarker>>,
+    time: Res<Time>,
+    mut camera_transform: Query<(Entity, &mut Transform), With<CameraMarker>>,
+) {
+    let movement = player_input.camera_vector();
+    if movement == Vec2::ZERO {
+        return;
+    }
+    let (entity, mut transform) = camera_transform.single_mut();
+    transform.translation += movement.extend(0.) * camera_speed.0 * time.delta_seconds();
+    commands.entity(entity).remove::<ZoomAnchor>();
+}
+
+/// This System zooms in or out with the mouse wheel, keeping the point under the cursor in place
+fn zoom_to_cursor(
+    mut commands: Commands,
+    player_input: Res<PlayerInput>,
+    camera_settings: Res<CameraSettings>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut camera: Query<(Entity, &Camera, &GlobalTransform, &mut TimeEase), With<CameraMarker>>,
+) {
+    let scroll = player_input.scroll();
+    if scroll == 0. {
+        return;
+    }
+    let window = windows.single();
+    let Some(cursor_position) = window.cursor_position() else {
+        return;
+    };
+    let (entity, camera, camera_transform, mut time_ease) = camera.single_mut();
+    let Some(world_position) = camera.viewport_to_world_2d(camera_transform, cursor_position)
+    else {
+        return;
+    };
+    let goal_zoom = (time_ease.get_end_val() * camera_settings.zoom_step.powf(scroll))
+        .clamp(camera_settings.min_zoom, camera_settings.max_zoom);
+    time_ease.set_ease(
+        goal_zoom,
+        EasingFunction::Sine,
+        EasingType::Out,
+        camera_settings.zoom_duration,
+    );
+    commands.entity(entity).insert(ZoomAnchor {
+        world_position,
+        viewport_offset: Vec2::new(
+            cursor_position.x - window.width() / 2.,
+            window.height() / 2. - cursor_position.y,
+        ),
+    });
+}
+
+/// This System drags the camera around while the middle mouse button is held
+fn pan_with_mouse(
+    mut commands: Commands,
+    player_input: Res<PlayerInput>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut last_cursor_position: Local<Option<Vec2>>,
+    mut camera: Query<(Entity, &mut Transform, &OrthographicProjection), With<CameraMarker>>,
 ) {
-    camera_transform.single_mut().translation +=
-        player_input.camera_vector().extend(0.) * camera_speed.0;
+    if !player_input.middle_click_held() {
+        *last_cursor_position = None;
+        return;
+    }
+    let Some(cursor_position) = windows.single().cursor_position() else {
+        return;
+    };
+    if let Some(last) = *last_cursor_position {
+        let (entity, mut transform, projection) = camera.single_mut();
+        let delta = cursor_position - last;
+        transform.translation.x -= delta.x * projection.scale;
+        transform.translation.y += delta.y * projection.scale;
+        commands.entity(entity).remove::<ZoomAnchor>();
+    }
+    *last_cursor_position = Some(cursor_position);
+}
+
+/// This System centers the camera on the level and zooms so the whole level is visible
+fn zoom_to_fit(
+    mut commands: Commands,
+    player_input: Res<PlayerInput>,
+    camera_settings: Res<CameraSettings>,
+    level: Res<Level>,
+    ls: Res<LevelSettings>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut camera: Query<(Entity, &mut Transform, &mut TimeEase), With<CameraMarker>>,
+) {
+    if !player_input.zoom_to_fit_pressed() {
+        return;
+    }
+    let spawn = ls.spawn_location.translation.truncate();
+    let bounds = level
+        .bounds()
+        .map_or(Rect::from_center_size(spawn, Vec2::ZERO), |bounds| {
+            bounds.union_point(spawn)
+        });
+    let size = bounds.size().max(Vec2::splat(16.));
+    let window = windows.single();
+    let goal_zoom = ((window.width() / size.x).min(window.height() / size.y) * FIT_MARGIN)
+        .clamp(camera_settings.min_zoom, camera_settings.max_zoom);
+    let (entity, mut transform, mut time_ease) = camera.single_mut();
+    transform.translation = bounds.center().extend(transform.translation.z);
+    time_ease.set_ease(
+        goal_zoom,
+        EasingFunction::Sine,
+        EasingType::InOut,
+        camera_settings.zoom_duration,
+    );
+    commands.entity(entity).remove::<ZoomAnchor>();
 }
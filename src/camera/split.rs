@@ -0,0 +1,182 @@
+use super::{
+    movement::{
+        follow::{FollowMarker, Target},
+        MovementMode,
+    },
+    CameraMarker,
+};
+use crate::config::CameraSettings;
+use crate::input::PlayerInput;
+use crate::player::PlayerNumber;
+use crate::states::AppState;
+use crate::utilities::easing::{EasingFunction, EasingType, TimeEase};
+use bevy::{prelude::*, render::camera::Viewport, window::PrimaryWindow};
+
+/// The Plugin that splits the screen between two players, as an alternative to a shared camera
+pub struct SplitScreenPlugin;
+
+impl Plugin for SplitScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ScreenLayout>()
+            .add_systems(
+                Update,
+                (cycle_screen_layout, update_split_cameras)
+                    .chain()
+                    .run_if(in_state(AppState::Playing)),
+            )
+            .add_systems(Update, update_viewports)
+            .add_systems(OnExit(AppState::Playing), remove_split_cameras);
+    }
+}
+
+/// The Resource that decides how the screen is shared while two players play
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ScreenLayout {
+    /// Both players are shown by the same camera, which zooms out to keep them in frame
+    #[default]
+    Shared,
+    /// The screen is split by a vertical line, with the first player on the left
+    Vertical,
+    /// The screen is split by a horizontal line, with the first player on top
+    Horizontal,
+}
+
+impl ScreenLayout {
+    /// Returns the layout that follows this one when cycling through them
+    fn next(self) -> Self {
+        match self {
+            ScreenLayout::Shared => ScreenLayout::Vertical,
+            ScreenLayout::Vertical => ScreenLayout::Horizontal,
+            ScreenLayout::Horizontal => ScreenLayout::Shared,
+        }
+    }
+
+    /// Returns the physical position and size of both halves of a window of the given physical size, if the screen is split
+    fn halves(self, size: UVec2) -> Option<[(UVec2, UVec2); 2]> {
+        let (half, offset) = match self {
+            ScreenLayout::Shared => return None,
+            ScreenLayout::Vertical => (UVec2::new(size.x / 2, size.y), UVec2::new(size.x / 2, 0)),
+            ScreenLayout::Horizontal => (UVec2::new(size.x, size.y / 2), UVec2::new(0, size.y / 2)),
+        };
+        // A minimized window has no room for a viewport
+        if half.min_element() == 0 {
+            return None;
+        }
+        Some([(UVec2::ZERO, half), (offset, half)])
+    }
+}
+
+/// Marks the camera that shows the second player while the screen is split. The first player is shown by the main camera
+#[derive(Component)]
+pub struct SplitCameraMarker;
+
+/// This System switches to the next [ScreenLayout] when asked to
+fn cycle_screen_layout(input: Res<PlayerInput>, mut layout: ResMut<ScreenLayout>) {
+    if input.cycle_screen_layout_pressed() {
+        *layout = layout.next();
+    }
+}
+
+/// This System gives the second player their own camera while the screen is split, and lets the main camera follow both players again otherwise
+fn update_split_cameras(
+    mut commands: Commands,
+    layout: Res<ScreenLayout>,
+    camera_settings: Res<CameraSettings>,
+    players: Query<&PlayerNumber>,
+    main_camera: Query<(Entity, &Transform), With<CameraMarker>>,
+    split_cameras: Query<Entity, With<SplitCameraMarker>>,
+) {
+    let split = *layout != ScreenLayout::Shared && players.iter().any(|number| number.0 == 1);
+    if split != split_cameras.is_empty() {
+        return;
+    }
+    let Ok((main_camera, transform)) = main_camera.get_single() else {
+        return;
+    };
+    if !split {
+        commands
+            .entity(main_camera)
+            .insert(FollowMarker::all(Target::players()));
+        split_cameras.iter().for_each(|entity| {
+            commands.entity(entity).despawn();
+        });
+        return;
+    }
+    commands
+        .entity(main_camera)
+        .insert(FollowMarker::new(Target::Player(0)));
+    commands.spawn((
+        Camera2dBundle {
+            camera: Camera {
+                order: 1,
+                // The main camera already cleared the whole window
+                clear_color: ClearColorConfig::None,
+                ..default()
+            },
+            transform: *transform,
+            ..default()
+        },
+        SplitCameraMarker,
+        TimeEase::new(
+            0,
+            camera_settings.zoom_duration,
+            camera_settings.default_zoom,
+            camera_settings.default_zoom,
+            EasingFunction::Sine,
+            EasingType::Out,
+        ),
+        FollowMarker::new(Target::Player(1)),
+        MovementMode::Follow,
+        Name::new("Split Screen Camera"),
+    ));
+}
+
+/// This System fits the viewports of the main camera and the split screen camera to their halves of the window, or gives the whole window back to the main camera
+fn update_viewports(
+    layout: Res<ScreenLayout>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut main_camera: Query<&mut Camera, (With<CameraMarker>, Without<SplitCameraMarker>)>,
+    mut split_cameras: Query<&mut Camera, With<SplitCameraMarker>>,
+) {
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    let halves = if split_cameras.is_empty() {
+        None
+    } else {
+        layout.halves(window.physical_size())
+    };
+    main_camera.iter_mut().for_each(|camera| {
+        fit_viewport(camera, halves.map(|[first, _]| first));
+    });
+    split_cameras.iter_mut().for_each(|camera| {
+        fit_viewport(camera, halves.map(|[_, second]| second));
+    });
+}
+
+/// Helper function that gives the camera a viewport at the given physical position and size, or the whole window if there is none
+///
+/// The viewport is only touched if it changes, so the camera isn't marked as changed every frame
+fn fit_viewport(mut camera: Mut<Camera>, half: Option<(UVec2, UVec2)>) {
+    let current = camera
+        .viewport
+        .as_ref()
+        .map(|viewport| (viewport.physical_position, viewport.physical_size));
+    if current != half {
+        camera.viewport = half.map(|(physical_position, physical_size)| Viewport {
+            physical_position,
+            physical_size,
+            ..default()
+        });
+    }
+}
+
+/// This System removes the split screen camera once the game stops being played
+fn remove_split_cameras(
+    mut commands: Commands,
+    split_cameras: Query<Entity, With<SplitCameraMarker>>,
+) {
+    split_cameras.iter().for_each(|entity| {
+        commands.entity(entity).despawn();
+    });
+}
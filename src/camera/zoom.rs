@@ -1,5 +1,10 @@
-use super::CameraMarker;
-use crate::utilities::easing::TimeEase;
+use super::{
+    movement::follow::{FollowMarker, TargetMarker},
+    CameraMarker,
+};
+use crate::config::CameraSettings;
+use crate::states::AppState;
+use crate::utilities::easing::{EasingFunction, EasingType, TimeEase};
 use bevy::prelude::*;
 
 /// The Plugin containing everything related to zooming
@@ -7,14 +12,108 @@ pub struct ZoomPlugin;
 
 impl Plugin for ZoomPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, update_zoom);
+        app.add_systems(
+            Update,
+            (
+                frame_targets.run_if(in_state(AppState::Playing)),
+                update_zoom,
+                keep_zoom_anchor,
+            )
+                .chain(),
+        )
+        .add_systems(OnEnter(AppState::Playing), reset_zoom);
     }
 }
 
+/// Keeps a world position at the same spot on screen while the zoom is animated
+#[derive(Component)]
+pub struct ZoomAnchor {
+    pub world_position: Vec2,
+    /// The anchors offset from the center of the viewport in logical pixels, with y pointing up
+    pub viewport_offset: Vec2,
+}
+
+/// How much the zoom that frames every target may differ from the current goal, as a fraction of it, before the camera zooms again
+const FRAMING_TOLERANCE: f32 = 0.02;
+
 // Systems
 
-/// System that manages the cameras zoom
-fn update_zoom(mut query: Query<(&mut OrthographicProjection, &TimeEase), With<CameraMarker>>) {
-    let (mut projection, time_ease) = query.single_mut();
-    projection.scale = 1. / time_ease.get_current_value();
+/// This System zooms every camera out as the targets it follows move apart, and back in as they come together, so all of them stay in frame
+///
+/// It never zooms in further than the default zoom, which is what a single player is shown at.
+/// Every camera frames its own targets in its own viewport, so the cameras of a split screen zoom independently
+fn frame_targets(
+    camera_settings: Res<CameraSettings>,
+    targets: Query<(&TargetMarker, &Transform)>,
+    mut cameras: Query<(&Camera, &FollowMarker, &mut TimeEase)>,
+) {
+    cameras
+        .iter_mut()
+        .for_each(|(camera, follow_marker, mut time_ease)| {
+            let (Some(viewport), Some((min, max))) =
+                (camera.logical_viewport_size(), follow_marker.span(&targets))
+            else {
+                return;
+            };
+            let size = (max - min).truncate() + Vec2::splat(2. * camera_settings.framing_margin);
+            let goal_zoom = (viewport / size)
+                .min_element()
+                .clamp(camera_settings.min_zoom, camera_settings.default_zoom);
+            if (goal_zoom - time_ease.get_end_val()).abs() <= goal_zoom * FRAMING_TOLERANCE {
+                return;
+            }
+            time_ease.set_ease(
+                goal_zoom,
+                EasingFunction::Sine,
+                EasingType::Out,
+                camera_settings.framing_duration,
+            );
+        });
+}
+
+/// System that manages the zoom of every camera
+fn update_zoom(mut query: Query<(&mut OrthographicProjection, &TimeEase)>) {
+    query.iter_mut().for_each(|(mut projection, time_ease)| {
+        projection.scale = 1. / time_ease.get_current_value();
+    });
+}
+
+/// System that moves the camera so its [ZoomAnchor] stays in place, and removes the anchor once zooming is done
+fn keep_zoom_anchor(
+    mut commands: Commands,
+    mut query: Query<
+        (
+            Entity,
+            &mut Transform,
+            &OrthographicProjection,
+            &TimeEase,
+            &ZoomAnchor,
+        ),
+        With<CameraMarker>,
+    >,
+) {
+    query
+        .iter_mut()
+        .for_each(|(entity, mut transform, projection, time_ease, anchor)| {
+            let position = anchor.world_position - anchor.viewport_offset * projection.scale;
+            transform.translation = position.extend(transform.translation.z);
+            if time_ease.is_done() {
+                commands.entity(entity).remove::<ZoomAnchor>();
+            }
+        });
+}
+
+/// System that returns to the default zoom when the game starts being played
+fn reset_zoom(
+    camera_settings: Res<CameraSettings>,
+    mut query: Query<&mut TimeEase, With<CameraMarker>>,
+) {
+    query.iter_mut().for_each(|mut time_ease| {
+        time_ease.set_ease(
+            camera_settings.default_zoom,
+            EasingFunction::Sine,
+            EasingType::InOut,
+            camera_settings.zoom_duration,
+        );
+    });
 }
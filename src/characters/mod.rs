@@ -0,0 +1,148 @@
+mod selection;
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::Collider;
+use bevy_tnua_rapier2d::TnuaRapier2dSensorShape;
+use selection::CharacterSelectionPlugin;
+use serde::Deserialize;
+use std::fs::read_to_string;
+
+use crate::{
+    config::{CharacterControllerSettings, CharacterSettings, PlayerSettings},
+    player::{JumpHeight, PlayerColliders, Speed},
+    utilities::{animation::SpriteAnimation, assets::PlayerAnimationState},
+};
+
+/// The Plugin containing the selectable player characters
+pub struct CharacterPlugin;
+
+impl Plugin for CharacterPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CharacterRegistry>()
+            .init_resource::<SelectedCharacter>()
+            .add_systems(Startup, load_character_registry)
+            .add_plugins(CharacterSelectionPlugin);
+    }
+}
+
+/// A character the player can play as
+#[derive(Clone, Deserialize)]
+pub struct Character {
+    /// The name the character is remembered by in the settings file
+    pub id: String,
+    /// The name shown on the selection screen
+    pub name: String,
+    /// The set of clips in the animation file the character is animated with
+    pub animations: String,
+    /// The color the sprite sheets are tinted with, which makes a skin out of another characters sprite sheets
+    #[serde(default)]
+    pub tint: Option<(f32, f32, f32)>,
+    /// The collider sizes of the character, instead of the ones in [PlayerSettings]
+    #[serde(default)]
+    pub colliders: Option<PlayerColliders>,
+    /// The speed of the character, instead of the one in [PlayerSettings]
+    #[serde(default)]
+    pub speed: Option<f32>,
+    /// The full jump height of the character, instead of the one in [CharacterControllerSettings]
+    #[serde(default)]
+    pub jump_height: Option<f32>,
+}
+
+impl Default for Character {
+    fn default() -> Self {
+        Character {
+            id: "adventurer".to_string(),
+            name: "Adventurer".to_string(),
+            animations: "player".to_string(),
+            tint: None,
+            colliders: None,
+            speed: None,
+            jump_height: None,
+        }
+    }
+}
+
+impl Character {
+    /// Returns the color the sprite of the character is drawn with
+    pub fn color(&self) -> Color {
+        self.tint.map_or(Color::WHITE, |(red, green, blue)| {
+            Color::srgb(red, green, blue)
+        })
+    }
+
+    /// Returns the components that make a player entity look and move like this character
+    pub fn components(
+        &self,
+        ps: &PlayerSettings,
+        ccs: &CharacterControllerSettings,
+    ) -> (
+        Speed,
+        JumpHeight,
+        PlayerColliders,
+        Collider,
+        TnuaRapier2dSensorShape,
+        SpriteAnimation,
+    ) {
+        let colliders = self.colliders.unwrap_or(ps.colliders);
+        (
+            self.speed.map_or(ps.speed, Speed),
+            JumpHeight(self.jump_height.unwrap_or(ccs.max_jump_height)),
+            colliders,
+            colliders.collider(false),
+            TnuaRapier2dSensorShape(colliders.sensor_shape(false)),
+            SpriteAnimation::new(self.animations.clone(), PlayerAnimationState::IDLE.as_ref()),
+        )
+    }
+}
+
+/// The Resource holding every selectable character
+#[derive(Resource)]
+pub struct CharacterRegistry(pub Vec<Character>);
+
+impl Default for CharacterRegistry {
+    fn default() -> Self {
+        CharacterRegistry(vec![Character::default()])
+    }
+}
+
+impl CharacterRegistry {
+    /// Returns the character with the given id, falling back to the first one if there is none
+    pub fn get(&self, id: &str) -> Character {
+        self.0
+            .iter()
+            .find(|character| character.id == id)
+            .or(self.0.first())
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+/// The Resource holding the id of the character the player plays as
+#[derive(Resource)]
+pub struct SelectedCharacter(pub String);
+
+impl Default for SelectedCharacter {
+    fn default() -> Self {
+        SelectedCharacter(Character::default().id)
+    }
+}
+
+/// This System reads the selectable characters from the character file
+fn load_character_registry(
+    character_settings: Res<CharacterSettings>,
+    mut registry: ResMut<CharacterRegistry>,
+) {
+    let characters: Vec<Character> = match read_to_string(&character_settings.registry_path)
+        .map_err(|e| e.to_string())
+        .and_then(|json| serde_json::from_str(&json).map_err(|e| e.to_string()))
+    {
+        Ok(characters) => characters,
+        Err(e) => {
+            eprintln!("Failed to load characters: {}", e);
+            return;
+        }
+    };
+    if !characters.is_empty() {
+        registry.0 = characters;
+    }
+}
@@ -0,0 +1,177 @@
+use bevy::prelude::*;
+
+use super::{CharacterRegistry, SelectedCharacter};
+use crate::{
+    config::{CharacterControllerSettings, PlayerSettings, SaveSettings},
+    input::PlayerInput,
+    player::{CrouchState, PlayerNumber},
+    settings::update_settings_file,
+};
+
+/// The Plugin containing the screen that lets players choose their character
+pub struct CharacterSelectionPlugin;
+
+impl Plugin for CharacterSelectionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (toggle_screen, press_buttons, update_labels).chain(),
+        );
+    }
+}
+
+/// Marks the root node of the character selection screen
+#[derive(Component)]
+struct SelectionScreenMarker;
+
+/// A button that selects the character with the given id
+#[derive(Component)]
+struct CharacterButton(String);
+
+/// Helper function that spawns the character selection screen, with a button per character
+fn spawn_screen(commands: &mut Commands, registry: &CharacterRegistry) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    padding: UiRect::all(Val::Px(20.)),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(10.),
+                    ..default()
+                },
+                background_color: Color::srgba(0., 0., 0., 0.85).into(),
+                z_index: ZIndex::Global(50),
+                ..default()
+            },
+            Interaction::default(),
+            SelectionScreenMarker,
+            Name::new("Character Selection Screen"),
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Characters - click a character to play as it. Escape closes.",
+                TextStyle {
+                    font_size: 20.,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ));
+            registry.0.iter().for_each(|character| {
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                width: Val::Px(240.),
+                                padding: UiRect::all(Val::Px(4.)),
+                                ..default()
+                            },
+                            background_color: Color::srgba(1., 1., 1., 0.1).into(),
+                            ..default()
+                        },
+                        CharacterButton(character.id.clone()),
+                    ))
+                    .with_children(|button| {
+                        button.spawn(TextBundle::from_section(
+                            character.name.clone(),
+                            TextStyle {
+                                font_size: 16.,
+                                color: character.color(),
+                                ..default()
+                            },
+                        ));
+                    });
+            });
+        });
+}
+
+/// This System opens and closes the character selection screen
+fn toggle_screen(
+    mut commands: Commands,
+    input: Res<PlayerInput>,
+    registry: Res<CharacterRegistry>,
+    query: Query<Entity, With<SelectionScreenMarker>>,
+) {
+    let open = !query.is_empty();
+    let close = open && input.cancel_pressed();
+    if !input.open_characters_pressed() && !close {
+        return;
+    }
+    if open {
+        query.iter().for_each(|entity| {
+            commands.entity(entity).despawn_recursive();
+        });
+    } else {
+        spawn_screen(&mut commands, &registry);
+    }
+}
+
+/// This System selects the character whose button is pressed, remembers it in the settings file and applies it to every player
+#[allow(clippy::too_many_arguments)]
+fn press_buttons(
+    mut commands: Commands,
+    ps: Res<PlayerSettings>,
+    ccs: Res<CharacterControllerSettings>,
+    save_settings: Res<SaveSettings>,
+    registry: Res<CharacterRegistry>,
+    mut selected: ResMut<SelectedCharacter>,
+    buttons: Query<(&Interaction, &CharacterButton), Changed<Interaction>>,
+    mut players: Query<(Entity, &PlayerNumber, &mut Sprite, &mut CrouchState)>,
+) {
+    let Some(id) = buttons
+        .iter()
+        .find(|(interaction, _)| **interaction == Interaction::Pressed)
+        .map(|(_, button)| button.0.clone())
+    else {
+        return;
+    };
+    if let Err(e) = update_settings_file(&save_settings.settings_path, |settings| {
+        settings.character = Some(id.clone());
+    }) {
+        eprintln!("Failed to save character: {}", e);
+    }
+    let character = registry.get(&id);
+    selected.0 = id;
+    // Players that are already in the level switch right away, standing up with the new colliders
+    players
+        .iter_mut()
+        .for_each(|(entity, number, mut sprite, mut crouch_state)| {
+            *crouch_state = CrouchState::default();
+            *sprite = Sprite {
+                color: number.color(character.color(), &ps),
+                flip_x: sprite.flip_x,
+                ..default()
+            };
+            commands
+                .entity(entity)
+                .insert(character.components(&ps, &ccs));
+        });
+}
+
+/// This System marks the button of the selected character
+fn update_labels(
+    selected: Res<SelectedCharacter>,
+    registry: Res<CharacterRegistry>,
+    buttons: Query<(&CharacterButton, &Children)>,
+    added: Query<(), Added<CharacterButton>>,
+    mut texts: Query<&mut Text>,
+) {
+    if !selected.is_changed() && added.is_empty() {
+        return;
+    }
+    buttons.iter().for_each(|(button, children)| {
+        let character = registry.get(&button.0);
+        let label = if button.0 == selected.0 {
+            format!("> {}", character.name)
+        } else {
+            character.name
+        };
+        children.iter().for_each(|&child| {
+            if let Ok(mut text) = texts.get_mut(child) {
+                text.sections[0].value.clone_from(&label);
+            }
+        });
+    });
+}
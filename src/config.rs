@@ -1,7 +1,11 @@
 use bevy::prelude::*;
-use bevy_tnua::prelude::TnuaBuiltinWalk;
+use bevy_tnua::{
+    builtins::{TnuaBuiltinCrouch, TnuaBuiltinDash},
+    prelude::{TnuaBuiltinJump, TnuaBuiltinWalk},
+};
 
-use crate::player::{JumpHeight, PlayerBundle, Speed};
+use crate::player::{PlayerBundle, PlayerColliders, Speed, MAX_PLAYERS};
+use crate::utilities::assets::Material;
 
 /// The Plugin that allows for configuration
 pub struct ConfigPlugin;
@@ -10,8 +14,15 @@ impl Plugin for ConfigPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(CharacterControllerSettings::default())
             .insert_resource(PlayerSettings::default())
+            .insert_resource(WallSettings::default())
+            .insert_resource(AnimationSettings::default())
+            .insert_resource(CharacterSettings::default())
             .insert_resource(LevelSettings::default())
-            .insert_resource(CameraSettings::default());
+            .insert_resource(CameraSettings::default())
+            .insert_resource(SaveSettings::default())
+            .insert_resource(PropSettings::default())
+            .insert_resource(TerrainSettings::default())
+            .insert_resource(InputSettings::default());
     }
 }
 
@@ -19,6 +30,30 @@ impl Plugin for ConfigPlugin {
 #[derive(Resource)]
 pub struct CharacterControllerSettings {
     pub builtin_walk: TnuaBuiltinWalk,
+    /// The jump every player jump is based on. Its extra gravities are set from the gravity multipliers below
+    pub builtin_jump: TnuaBuiltinJump,
+    /// How many times the gravity pulls on the player while they fall from a jump
+    pub fall_gravity_multiplier: f32,
+    /// How many times the gravity pulls on the player while a jump is cut short because jump was released
+    pub release_gravity_multiplier: f32,
+    /// The height of a jump when jump is only tapped
+    pub min_jump_height: f32,
+    /// The height of a jump when jump is held until its peak, for characters that don't bring their own
+    pub max_jump_height: f32,
+    /// The crouch every player crouch is based on
+    pub builtin_crouch: TnuaBuiltinCrouch,
+    /// The fraction of their speed the player moves with while crouching
+    pub crawl_speed_factor: f32,
+    /// How many seconds a crouch started while running slides along, before slowing down to crawl speed
+    pub crouch_slide_time: f32,
+    /// The dash every player dash is based on. Tnua ignores gravity while it is going on
+    pub builtin_dash: TnuaBuiltinDash,
+    /// How far a dash moves the player
+    pub dash_distance: f32,
+    /// How many seconds a jump pressed in the air is remembered, so it still happens when landing shortly after
+    pub jump_buffer_time: f32,
+    /// How many seconds after walking off a ledge the player can still jump
+    pub coyote_time: f32,
 }
 
 impl Default for CharacterControllerSettings {
@@ -31,6 +66,28 @@ impl Default for CharacterControllerSettings {
                 air_acceleration: 5000.,
                 ..default()
             },
+            builtin_jump: TnuaBuiltinJump {
+                allow_in_air: true,
+                ..default()
+            },
+            fall_gravity_multiplier: 1.5,
+            release_gravity_multiplier: 2.5,
+            min_jump_height: 12.,
+            max_jump_height: 40.,
+            builtin_crouch: TnuaBuiltinCrouch::default(),
+            crawl_speed_factor: 0.4,
+            crouch_slide_time: 0.35,
+            builtin_dash: TnuaBuiltinDash {
+                allow_in_air: true,
+                speed: 600.,
+                brake_to_speed: 200.,
+                acceleration: 20000.,
+                brake_acceleration: 10000.,
+                ..default()
+            },
+            dash_distance: 64.,
+            jump_buffer_time: 0.12,
+            coyote_time: 0.1,
         }
     }
 }
@@ -39,21 +96,107 @@ impl Default for CharacterControllerSettings {
 #[derive(Resource)]
 pub struct PlayerSettings {
     pub player_bundle: PlayerBundle,
-    pub sensor_collider_size: Vec2,
-    pub collider_size: Vec2,
+    /// The speed of characters that don't bring their own
+    pub speed: Speed,
+    /// The collider sizes of characters that don't bring their own
+    pub colliders: PlayerColliders,
+    /// The fraction of its height the sprite is squashed to while crouching
+    pub crouch_sprite_height: f32,
+    /// How far every player after the first spawns from the one before them
+    pub spawn_offset: Vec3,
+    /// The color mixed into the sprite of every player, indexed by their [PlayerNumber](crate::player::PlayerNumber), so players of the same character can be told apart
+    pub player_tints: [Option<Color>; MAX_PLAYERS],
 }
 
 impl Default for PlayerSettings {
     fn default() -> Self {
         PlayerSettings {
             player_bundle: PlayerBundle {
-                jump_height: JumpHeight(40.),
-                speed: Speed(200.),
                 name: Name::new("Player"),
                 ..default()
             },
-            collider_size: Vec2::new(12., 14.),
-            sensor_collider_size: Vec2::new(11.5, 13.5),
+            speed: Speed(200.),
+            colliders: PlayerColliders {
+                collider_size: Vec2::new(12., 14.),
+                sensor_collider_size: Vec2::new(11.5, 13.5),
+                crouch_collider_size: Vec2::new(12., 2.),
+                crouch_sensor_collider_size: Vec2::new(11.5, 1.5),
+            },
+            crouch_sprite_height: 0.65,
+            spawn_offset: Vec3::new(24., 0., 0.),
+            player_tints: [None, Some(Color::srgb(0.4, 0.7, 1.))],
+        }
+    }
+}
+
+/// The Resource that configures how the player slides down and jumps off walls
+#[derive(Resource)]
+pub struct WallSettings {
+    /// How far beside the collider a wall is still detected
+    pub detection_distance: f32,
+    /// The fastest the player falls while pressing into a wall
+    pub slide_speed: f32,
+    /// The speed the player is pushed away from a wall with when jumping off it
+    pub jump_speed: f32,
+    /// The angle of a wall jump above the horizontal, in radians
+    pub jump_angle: f32,
+    /// How many seconds after a wall jump the horizontal input is ignored, so the player can't immediately walk back to the wall
+    pub jump_lockout: f32,
+}
+
+impl Default for WallSettings {
+    fn default() -> Self {
+        WallSettings {
+            detection_distance: 1.5,
+            slide_speed: 60.,
+            jump_speed: 330.,
+            jump_angle: 60_f32.to_radians(),
+            jump_lockout: 0.18,
+        }
+    }
+}
+
+/// The Resource that configures the animations, and when the player animation switches between states
+#[derive(Resource)]
+pub struct AnimationSettings {
+    /// The file the animation clips are loaded from
+    pub clip_path: String,
+    /// The horizontal speed above which a grounded player counts as running
+    pub run_speed: f32,
+    /// The vertical speed above which an airborne player counts as rising. Below it they count as falling
+    pub rise_speed: f32,
+    /// The speed the player has to hit the ground with for the landing animation to play
+    pub landing_speed: f32,
+    /// How many seconds the landing animation is shown
+    pub landing_time: f32,
+    /// The speed a grounded player has to move against the direction they face with to count as turning around
+    pub turn_speed: f32,
+}
+
+impl Default for AnimationSettings {
+    fn default() -> Self {
+        AnimationSettings {
+            clip_path: "assets/animations.json".to_string(),
+            run_speed: 10.,
+            rise_speed: 0.,
+            landing_speed: 150.,
+            landing_time: 0.1,
+            turn_speed: 40.,
+        }
+    }
+}
+
+/// The Resource that configures where the selectable characters come from
+#[derive(Resource)]
+pub struct CharacterSettings {
+    /// The file listing every selectable character
+    pub registry_path: String,
+}
+
+impl Default for CharacterSettings {
+    fn default() -> Self {
+        CharacterSettings {
+            registry_path: "assets/characters.json".to_string(),
         }
     }
 }
@@ -63,6 +206,8 @@ impl Default for PlayerSettings {
 pub struct LevelSettings {
     pub death_height: f32,
     pub spawn_location: Transform,
+    /// How many seconds have to pass between two dashes. A dash used in the air is only refreshed once the player lands
+    pub dash_cooldown: f32,
 }
 
 impl Default for LevelSettings {
@@ -70,6 +215,7 @@ impl Default for LevelSettings {
         LevelSettings {
             death_height: -100.,
             spawn_location: Transform::from_xyz(0., 30., 10.),
+            dash_cooldown: 0.5,
         }
     }
 }
@@ -78,10 +224,121 @@ impl Default for LevelSettings {
 #[derive(Resource)]
 pub struct CameraSettings {
     pub default_zoom: f32,
+    pub min_zoom: f32,
+    pub max_zoom: f32,
+    /// The factor the zoom changes by per mouse wheel step
+    pub zoom_step: f32,
+    /// How long a zoom animation takes, in milliseconds
+    pub zoom_duration: u16,
+    /// How much space is kept around the players while the camera zooms out to keep all of them in frame
+    pub framing_margin: f32,
+    /// How long the camera takes to zoom in or out when the players move apart or together, in milliseconds
+    pub framing_duration: u16,
+    /// How strongly the camera is pulled towards the players it follows. The pull is critically damped, so it settles as quickly as it can without overshooting
+    pub follow_stiffness: f32,
+    /// The size of the rectangle the followed players can move around in without moving the camera, in pixels
+    pub follow_dead_zone: Vec2,
+    /// How many seconds of their movement the camera looks ahead of the followed players
+    pub look_ahead_time: f32,
+    /// The furthest the camera looks ahead of the followed players, horizontally and vertically, in pixels
+    pub max_look_ahead: Vec2,
 }
 
 impl Default for CameraSettings {
     fn default() -> Self {
-        CameraSettings { default_zoom: 7. }
+        CameraSettings {
+            default_zoom: 7.,
+            min_zoom: 0.25,
+            max_zoom: 20.,
+            zoom_step: 1.25,
+            zoom_duration: 150,
+            framing_margin: 48.,
+            framing_duration: 400,
+            follow_stiffness: 100.,
+            follow_dead_zone: Vec2::new(32., 24.),
+            look_ahead_time: 0.25,
+            max_look_ahead: Vec2::new(64., 32.),
+        }
+    }
+}
+
+/// The Resource that configures saving the level and the users settings
+#[derive(Resource)]
+pub struct SaveSettings {
+    pub level_path: String,
+    pub recovery_path: String,
+    /// The file the users settings, like their key bindings, are stored in
+    pub settings_path: String,
+    /// Seconds between autosaves to the recovery file. Autosaving is disabled if this is not positive
+    pub autosave_interval: f32,
+}
+
+impl Default for SaveSettings {
+    fn default() -> Self {
+        SaveSettings {
+            level_path: "assets/level.json".to_string(),
+            recovery_path: "assets/level.recovery.json".to_string(),
+            settings_path: "settings.json".to_string(),
+            autosave_interval: 60.,
+        }
+    }
+}
+
+/// The Resource that configures the props placed in the editor
+#[derive(Resource)]
+pub struct PropSettings {
+    /// The directory whose images can be placed as props
+    pub directory: String,
+    /// The factor the scale of a prop changes by per key press
+    pub scale_step: f32,
+}
+
+impl Default for PropSettings {
+    fn default() -> Self {
+        PropSettings {
+            directory: "assets/props".to_string(),
+            scale_step: 1.25,
+        }
+    }
+}
+
+/// The Resource that configures the editors terrain tool
+#[derive(Resource)]
+pub struct TerrainSettings {
+    /// The material below the surface. `None` uses the grass material of the surface, whose lower tiles are plain dirt
+    pub fill_material: Option<Material>,
+    /// The grass material used when the selected material has no grass on top
+    pub default_grass: Material,
+    /// The size of the features of the cave noise, in blocks
+    pub cave_scale: f32,
+    /// Noise values above this become caves. Lower values carve more
+    pub cave_threshold: f32,
+    pub cave_seed: u32,
+}
+
+impl Default for TerrainSettings {
+    fn default() -> Self {
+        TerrainSettings {
+            fill_material: None,
+            default_grass: Material::GRASS_GREEN,
+            cave_scale: 6.,
+            cave_threshold: 0.58,
+            cave_seed: 0,
+        }
+    }
+}
+
+/// The Resource that configures input handling
+#[derive(Resource)]
+pub struct InputSettings {
+    /// How far the gamepad stick has to be tilted, from 0 to 1, before it moves anything
+    pub stick_deadzone: f32,
+}
+
+impl Default for InputSettings {
+    fn default() -> Self {
+        InputSettings {
+            stick_deadzone: 0.2,
+        }
     }
 }
@@ -1,7 +1,11 @@
 use bevy::prelude::*;
 use bevy_rapier2d::dynamics::Velocity;
 
-use crate::{config::LevelSettings, player::PlayerMarker, states::AppState};
+use crate::{
+    config::{LevelSettings, PlayerSettings},
+    player::PlayerNumber,
+    states::{editing::playtest::Playtest, AppState},
+};
 
 /// The Plugin containing all game logic
 pub struct GameLogicPlugin;
@@ -15,14 +19,19 @@ impl Plugin for GameLogicPlugin {
     }
 }
 
-/// A System that kills the player if he falls
+/// A System that kills every player that falls, respawning them on their own without affecting the others
 fn check_player_position(
     ls: Res<LevelSettings>,
-    mut query: Query<(&mut Transform, &mut Velocity), With<PlayerMarker>>,
+    ps: Res<PlayerSettings>,
+    playtest: Res<Playtest>,
+    mut query: Query<(&PlayerNumber, &mut Transform, &mut Velocity)>,
 ) {
-    let (mut transform, mut velocity) = query.single_mut();
-    if transform.translation.y < ls.death_height {
-        *transform = ls.spawn_location;
-        velocity.linvel = Vec2::ZERO;
-    }
+    query
+        .iter_mut()
+        .for_each(|(number, mut transform, mut velocity)| {
+            if transform.translation.y < ls.death_height {
+                *transform = number.spawn_location(playtest.spawn_location(&ls), &ps);
+                velocity.linvel = Vec2::ZERO;
+            }
+        });
 }
@@ -0,0 +1,429 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt};
+use strum::IntoEnumIterator;
+use strum_macros::{AsRefStr, EnumIter};
+
+use crate::player::MAX_PLAYERS;
+
+/// Everything the player can do with a key or mouse button
+#[derive(Hash, PartialEq, Eq, Copy, Clone, EnumIter, AsRefStr, Debug, Serialize, Deserialize)]
+pub enum Action {
+    Jump,
+    Crouch,
+    Dash,
+    Left,
+    Right,
+    /// The jump of the second player in co-op
+    Player2Jump,
+    /// The crouch of the second player in co-op
+    Player2Crouch,
+    /// The dash of the second player in co-op
+    Player2Dash,
+    /// Moving left as the second player in co-op
+    Player2Left,
+    /// Moving right as the second player in co-op
+    Player2Right,
+    ChangeMode,
+    PlayFromHere,
+    ToggleGrid,
+    ZoomToFit,
+    CycleLayer,
+    CycleTool,
+    CycleBrush,
+    ToggleMirror,
+    Place,
+    Select,
+    Pan,
+    /// Held down for the [Save](Action::Save) and [Undo](Action::Undo) shortcuts, and to change materials with the mouse wheel
+    Modifier,
+    Save,
+    Undo,
+    Replace,
+    Carve,
+    Delete,
+    Flip,
+    ScaleUp,
+    ScaleDown,
+    Raise,
+    Lower,
+    NextProp,
+    PreviousProp,
+    Confirm,
+    Deny,
+    Cancel,
+    OpenBindings,
+    OpenCharacters,
+    /// Lets a second player join or leave while playing
+    ToggleCoop,
+    /// Switches between a shared screen and a vertical or horizontal split screen while two players play
+    CycleScreenLayout,
+    Palette1,
+    Palette2,
+    Palette3,
+    Palette4,
+    Palette5,
+    Palette6,
+    Palette7,
+    Palette8,
+    Palette9,
+}
+
+/// The actions that select a palette slot, in slot order
+///
+/// There is one for each of the 8 materials plus the eraser. A material added beyond that gets a slot without a key,
+/// which the palette marks with "no key", so add another action here along with it
+pub const PALETTE_ACTIONS: [Action; 9] = [
+    Action::Palette1,
+    Action::Palette2,
+    Action::Palette3,
+    Action::Palette4,
+    Action::Palette5,
+    Action::Palette6,
+    Action::Palette7,
+    Action::Palette8,
+    Action::Palette9,
+];
+
+/// The actions of the second player in co-op. Their gamepad bindings are read from the gamepad of the second player instead of the first one
+pub const PLAYER2_ACTIONS: [Action; 5] = [
+    Action::Player2Jump,
+    Action::Player2Crouch,
+    Action::Player2Dash,
+    Action::Player2Left,
+    Action::Player2Right,
+];
+
+/// The actions that are only triggered while [Action::Modifier] is held. Their bindings don't trigger other actions meanwhile
+pub const SHORTCUT_ACTIONS: [Action; 2] = [Action::Save, Action::Undo];
+
+impl Action {
+    /// Returns the keys, mouse buttons and gamepad buttons the action is bound to by default
+    pub fn default_bindings(&self) -> Vec<Binding> {
+        use Binding::{Gamepad, Key, Mouse};
+        match self {
+            Action::Jump => vec![
+                Key(KeyCode::Space),
+                Key(KeyCode::ArrowUp),
+                Key(KeyCode::KeyW),
+                Gamepad(GamepadButtonType::South),
+            ],
+            Action::Crouch => vec![
+                Key(KeyCode::ShiftLeft),
+                Key(KeyCode::ShiftRight),
+                Key(KeyCode::ArrowDown),
+                Key(KeyCode::KeyS),
+                Gamepad(GamepadButtonType::East),
+                Gamepad(GamepadButtonType::DPadDown),
+            ],
+            Action::Dash => vec![Key(KeyCode::KeyK), Gamepad(GamepadButtonType::RightTrigger)],
+            Action::Left => vec![
+                Key(KeyCode::ArrowLeft),
+                Key(KeyCode::KeyA),
+                Gamepad(GamepadButtonType::DPadLeft),
+            ],
+            Action::Right => vec![
+                Key(KeyCode::ArrowRight),
+                Key(KeyCode::KeyD),
+                Gamepad(GamepadButtonType::DPadRight),
+            ],
+            Action::Player2Jump => vec![Key(KeyCode::Numpad8), Gamepad(GamepadButtonType::South)],
+            Action::Player2Crouch => vec![
+                Key(KeyCode::Numpad5),
+                Gamepad(GamepadButtonType::East),
+                Gamepad(GamepadButtonType::DPadDown),
+            ],
+            Action::Player2Dash => vec![
+                Key(KeyCode::Numpad0),
+                Gamepad(GamepadButtonType::RightTrigger),
+            ],
+            Action::Player2Left => {
+                vec![Key(KeyCode::Numpad4), Gamepad(GamepadButtonType::DPadLeft)]
+            }
+            Action::Player2Right => {
+                vec![Key(KeyCode::Numpad6), Gamepad(GamepadButtonType::DPadRight)]
+            }
+            Action::ChangeMode => vec![Key(KeyCode::KeyG), Gamepad(GamepadButtonType::Select)],
+            Action::PlayFromHere => vec![Key(KeyCode::KeyP)],
+            Action::ToggleGrid => vec![Key(KeyCode::KeyH)],
+            Action::ZoomToFit => vec![Key(KeyCode::KeyF)],
+            Action::CycleLayer => vec![Key(KeyCode::KeyL)],
+            Action::CycleTool => vec![Key(KeyCode::Tab)],
+            Action::CycleBrush => vec![Key(KeyCode::KeyB)],
+            Action::ToggleMirror => vec![Key(KeyCode::KeyM)],
+            Action::Place => vec![Mouse(MouseButton::Left)],
+            Action::Select => vec![Mouse(MouseButton::Right)],
+            Action::Pan => vec![Mouse(MouseButton::Middle)],
+            Action::Modifier => vec![Key(KeyCode::ControlLeft), Key(KeyCode::ControlRight)],
+            Action::Save => vec![Key(KeyCode::KeyS)],
+            Action::Undo => vec![Key(KeyCode::KeyZ)],
+            Action::Replace => vec![Key(KeyCode::KeyR)],
+            Action::Carve => vec![Key(KeyCode::KeyC)],
+            Action::Delete => vec![Key(KeyCode::Delete), Key(KeyCode::Backspace)],
+            Action::Flip => vec![Key(KeyCode::KeyX)],
+            Action::ScaleUp => vec![Key(KeyCode::Equal), Key(KeyCode::NumpadAdd)],
+            Action::ScaleDown => vec![Key(KeyCode::Minus), Key(KeyCode::NumpadSubtract)],
+            Action::Raise => vec![Key(KeyCode::PageUp)],
+            Action::Lower => vec![Key(KeyCode::PageDown)],
+            Action::NextProp => vec![Key(KeyCode::BracketRight)],
+            Action::PreviousProp => vec![Key(KeyCode::BracketLeft)],
+            Action::Confirm => vec![
+                Key(KeyCode::KeyY),
+                Key(KeyCode::Enter),
+                Gamepad(GamepadButtonType::Start),
+            ],
+            Action::Deny => vec![Key(KeyCode::KeyN), Gamepad(GamepadButtonType::North)],
+            Action::Cancel => vec![Key(KeyCode::Escape), Gamepad(GamepadButtonType::West)],
+            Action::OpenBindings => vec![Key(KeyCode::F1)],
+            Action::OpenCharacters => vec![Key(KeyCode::F2)],
+            Action::ToggleCoop => vec![Key(KeyCode::F3)],
+            Action::CycleScreenLayout => vec![Key(KeyCode::F4)],
+            Action::Palette1 => vec![Key(KeyCode::Digit1)],
+            Action::Palette2 => vec![Key(KeyCode::Digit2)],
+            Action::Palette3 => vec![Key(KeyCode::Digit3)],
+            Action::Palette4 => vec![Key(KeyCode::Digit4)],
+            Action::Palette5 => vec![Key(KeyCode::Digit5)],
+            Action::Palette6 => vec![Key(KeyCode::Digit6)],
+            Action::Palette7 => vec![Key(KeyCode::Digit7)],
+            Action::Palette8 => vec![Key(KeyCode::Digit8)],
+            Action::Palette9 => vec![Key(KeyCode::Digit9)],
+        }
+    }
+}
+
+/// A key, mouse button or gamepad button an [Action] can be bound to
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Serialize, Deserialize)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    /// A button on any connected gamepad
+    Gamepad(GamepadButtonType),
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Binding::Key(key) => write!(f, "{:?}", key),
+            Binding::Mouse(button) => write!(f, "Mouse {:?}", button),
+            Binding::Gamepad(button) => write!(f, "Gamepad {:?}", button),
+        }
+    }
+}
+
+/// The input resources of every device a [Binding] can refer to
+#[derive(SystemParam)]
+pub struct InputDevices<'w> {
+    pub kb: Res<'w, ButtonInput<KeyCode>>,
+    pub mouse: Res<'w, ButtonInput<MouseButton>>,
+    pub gamepads: Res<'w, Gamepads>,
+    pub gamepad_buttons: Res<'w, ButtonInput<GamepadButton>>,
+    pub gamepad_axes: Res<'w, Axis<GamepadAxis>>,
+}
+
+impl InputDevices<'_> {
+    /// Returns the number of the player the gamepad belongs to.
+    /// Gamepads are handed to the players in the order they were connected, any further ones belong to the first player
+    pub fn player_of(&self, gamepad: Gamepad) -> usize {
+        let index = self
+            .gamepads
+            .iter()
+            .filter(|other| other.id < gamepad.id)
+            .count();
+        if index < MAX_PLAYERS {
+            index
+        } else {
+            0
+        }
+    }
+
+    /// Returns whether the key or button of the binding is held down, on any gamepad
+    pub fn pressed(&self, binding: Binding) -> bool {
+        self.pressed_on(binding, |_| true)
+    }
+
+    /// Returns whether the key or button of the binding was pressed down this frame, on any gamepad
+    pub fn just_pressed(&self, binding: Binding) -> bool {
+        self.just_pressed_on(binding, |_| true)
+    }
+
+    /// Returns whether the key or button of the binding is held down, only counting the gamepads of the given player
+    pub fn pressed_by(&self, binding: Binding, player: usize) -> bool {
+        self.pressed_on(binding, |gamepad| self.player_of(gamepad) == player)
+    }
+
+    /// Returns whether the key or button of the binding was pressed down this frame, only counting the gamepads of the given player
+    pub fn just_pressed_by(&self, binding: Binding, player: usize) -> bool {
+        self.just_pressed_on(binding, |gamepad| self.player_of(gamepad) == player)
+    }
+
+    /// Helper function that returns whether the key or button of the binding is held down, on the gamepads that pass the filter
+    fn pressed_on(&self, binding: Binding, filter: impl Fn(Gamepad) -> bool) -> bool {
+        match binding {
+            Binding::Key(key) => self.kb.pressed(key),
+            Binding::Mouse(button) => self.mouse.pressed(button),
+            Binding::Gamepad(button) => self
+                .gamepads
+                .iter()
+                .filter(|gamepad| filter(*gamepad))
+                .any(|gamepad| {
+                    self.gamepad_buttons
+                        .pressed(GamepadButton::new(gamepad, button))
+                }),
+        }
+    }
+
+    /// Helper function that returns whether the key or button of the binding was pressed down this frame, on the gamepads that pass the filter
+    fn just_pressed_on(&self, binding: Binding, filter: impl Fn(Gamepad) -> bool) -> bool {
+        match binding {
+            Binding::Key(key) => self.kb.just_pressed(key),
+            Binding::Mouse(button) => self.mouse.just_pressed(button),
+            Binding::Gamepad(button) => self
+                .gamepads
+                .iter()
+                .filter(|gamepad| filter(*gamepad))
+                .any(|gamepad| {
+                    self.gamepad_buttons
+                        .just_pressed(GamepadButton::new(gamepad, button))
+                }),
+        }
+    }
+
+    /// Returns the first key or button pressed down this frame, if any
+    pub fn first_just_pressed(&self) -> Option<Binding> {
+        self.kb
+            .get_just_pressed()
+            .next()
+            .map(|key| Binding::Key(*key))
+            .or_else(|| {
+                self.mouse
+                    .get_just_pressed()
+                    .next()
+                    .map(|button| Binding::Mouse(*button))
+            })
+            .or_else(|| {
+                self.gamepad_buttons
+                    .get_just_pressed()
+                    .next()
+                    .map(|button| Binding::Gamepad(button.button_type))
+            })
+    }
+
+    /// Returns the position of the left stick of the first gamepad of the given player that moves it, from -1 to 1 on both axes
+    pub fn left_stick(&self, player: usize) -> Vec2 {
+        self.gamepads
+            .iter()
+            .filter(|gamepad| self.player_of(*gamepad) == player)
+            .map(|gamepad| {
+                let axis = |axis_type| {
+                    self.gamepad_axes
+                        .get(GamepadAxis::new(gamepad, axis_type))
+                        .unwrap_or(0.)
+                };
+                Vec2::new(
+                    axis(GamepadAxisType::LeftStickX),
+                    axis(GamepadAxisType::LeftStickY),
+                )
+            })
+            .find(|stick| *stick != Vec2::ZERO)
+            .unwrap_or(Vec2::ZERO)
+    }
+}
+
+/// The Resource mapping every [Action] to the keys and mouse buttons that trigger it
+#[derive(Resource, Clone, Serialize, Deserialize)]
+pub struct Bindings(pub HashMap<Action, Vec<Binding>>);
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Bindings(
+            Action::iter()
+                .map(|action| (action, action.default_bindings()))
+                .collect(),
+        )
+    }
+}
+
+impl Bindings {
+    /// Returns the bindings of the given [Action]
+    pub fn get(&self, action: Action) -> &[Binding] {
+        self.0.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns whether any binding of the [Action] is held down, on any gamepad
+    pub fn pressed(&self, action: Action, devices: &InputDevices) -> bool {
+        self.get(action)
+            .iter()
+            .any(|binding| devices.pressed(*binding))
+    }
+
+    /// Returns whether any binding of the [Action] was pressed down this frame, on any gamepad
+    pub fn just_pressed(&self, action: Action, devices: &InputDevices) -> bool {
+        self.get(action)
+            .iter()
+            .any(|binding| devices.just_pressed(*binding))
+    }
+
+    /// Returns whether any binding of the [Action] is held down by the given player, ignoring the reserved ones
+    pub fn pressed_by(
+        &self,
+        action: Action,
+        devices: &InputDevices,
+        player: usize,
+        reserved: &[Binding],
+    ) -> bool {
+        self.get(action)
+            .iter()
+            .filter(|binding| !reserved.contains(binding))
+            .any(|binding| devices.pressed_by(*binding, player))
+    }
+
+    /// Returns whether any binding of the [Action] was pressed down this frame by the given player, ignoring the reserved ones
+    pub fn just_pressed_by(
+        &self,
+        action: Action,
+        devices: &InputDevices,
+        player: usize,
+        reserved: &[Binding],
+    ) -> bool {
+        self.get(action)
+            .iter()
+            .filter(|binding| !reserved.contains(binding))
+            .any(|binding| devices.just_pressed_by(*binding, player))
+    }
+
+    /// Returns the bindings of the [SHORTCUT_ACTIONS], which belong to the shortcuts while [Action::Modifier] is held
+    pub fn shortcut_bindings(&self) -> Vec<Binding> {
+        SHORTCUT_ACTIONS
+            .iter()
+            .flat_map(|action| self.get(*action))
+            .copied()
+            .collect()
+    }
+
+    /// Returns the other actions that share a binding with the given one.
+    /// A shortcut and an action without the modifier don't conflict, as the modifier tells them apart,
+    /// and neither do the gamepad buttons of the two players, as each player has their own gamepad
+    pub fn conflicts(&self, action: Action) -> Vec<Action> {
+        let shortcut = SHORTCUT_ACTIONS.contains(&action);
+        let player2 = PLAYER2_ACTIONS.contains(&action);
+        Action::iter()
+            .filter(|other| *other != action && SHORTCUT_ACTIONS.contains(other) == shortcut)
+            .filter(|other| {
+                let same_player = PLAYER2_ACTIONS.contains(other) == player2;
+                self.get(*other).iter().any(|binding| {
+                    let shared_gamepad = matches!(binding, Binding::Gamepad(_)) && !same_player;
+                    self.get(action).contains(binding) && !shared_gamepad
+                })
+            })
+            .collect()
+    }
+
+    /// Adds the default bindings of every [Action] missing from the map, like ones added after the settings file was written
+    pub fn fill_missing(&mut self) {
+        Action::iter().for_each(|action| {
+            self.0
+                .entry(action)
+                .or_insert_with(|| action.default_bindings());
+        });
+    }
+}
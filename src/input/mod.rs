@@ -1,4 +1,14 @@
-use bevy::prelude::*;
+pub mod bindings;
+mod rebinding;
+
+use bevy::{
+    input::mouse::{MouseScrollUnit, MouseWheel},
+    prelude::*,
+};
+
+use crate::{config::InputSettings, player::MAX_PLAYERS};
+use bindings::{Action, Bindings, InputDevices, PALETTE_ACTIONS, SHORTCUT_ACTIONS};
+use rebinding::{RebindingPlugin, RebindingScreen};
 
 /// The Plugin containing everything related to input handling
 pub struct InputPlugin;
@@ -6,36 +16,130 @@ pub struct InputPlugin;
 impl Plugin for InputPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(Update, handle_input)
-            .insert_resource(PlayerInput::default());
+            .insert_resource(PlayerInput::default())
+            .init_resource::<PlayerInputs>()
+            .init_resource::<Bindings>()
+            .add_plugins(RebindingPlugin);
+    }
+}
+
+/// How many pixels of a high resolution scroll count as one line
+const PIXELS_PER_SCROLL_LINE: f32 = 20.;
+
+/// Helper function that ignores stick positions inside the deadzone and rescales the rest to reach from 0 to 1
+fn apply_deadzone(stick: Vec2, deadzone: f32) -> Vec2 {
+    let length = stick.length();
+    if length <= deadzone {
+        return Vec2::ZERO;
     }
+    let rescaled = ((length - deadzone) / (1. - deadzone)).min(1.);
+    stick / length * rescaled
 }
 
-/// A System that reads out the input resources
+/// A System that reads out the input resources, using the [Bindings] of every [Action]
 pub fn handle_input(
-    kb: Res<ButtonInput<KeyCode>>,
-    mouse: Res<ButtonInput<MouseButton>>,
+    devices: InputDevices,
+    mut mouse_wheel: EventReader<MouseWheel>,
+    input_settings: Res<InputSettings>,
+    bindings: Res<Bindings>,
+    rebinding_screen: Res<RebindingScreen>,
     mut player_input: ResMut<PlayerInput>,
+    mut player_inputs: ResMut<PlayerInputs>,
 ) {
-    player_input.jump = kb.just_pressed(KeyCode::Space)
-        || kb.just_pressed(KeyCode::ArrowUp)
-        || kb.just_pressed(KeyCode::KeyW);
-    player_input.up =
-        kb.pressed(KeyCode::Space) || kb.pressed(KeyCode::ArrowUp) || kb.pressed(KeyCode::KeyW);
-    player_input.crouch = kb.just_pressed(KeyCode::ShiftLeft)
-        || kb.just_pressed(KeyCode::ShiftRight)
-        || kb.just_pressed(KeyCode::ArrowDown)
-        || kb.just_pressed(KeyCode::KeyS);
-    player_input.down = kb.pressed(KeyCode::ShiftLeft)
-        || kb.pressed(KeyCode::ShiftRight)
-        || kb.pressed(KeyCode::ArrowDown)
-        || kb.pressed(KeyCode::KeyS);
-    player_input.left = kb.pressed(KeyCode::ArrowLeft) || kb.pressed(KeyCode::KeyA);
-    player_input.right = kb.pressed(KeyCode::ArrowRight) || kb.pressed(KeyCode::KeyD);
-    player_input.change_mode = kb.just_pressed(KeyCode::KeyG);
-    player_input.left_clicked = mouse.just_pressed(MouseButton::Left);
-    player_input.left_click_held = mouse.pressed(MouseButton::Left);
-    player_input.right_clicked = mouse.just_pressed(MouseButton::Right);
-    player_input.right_click_held = mouse.pressed(MouseButton::Right);
+    if rebinding_screen.is_open() {
+        mouse_wheel.clear();
+        *player_input = PlayerInput::default();
+        *player_inputs = PlayerInputs::default();
+        return;
+    }
+    // While the modifier is held, the keys of its shortcuts only trigger the shortcuts, so Ctrl+S saves without crouching
+    let reserved = if bindings.pressed(Action::Modifier, &devices) {
+        bindings.shortcut_bindings()
+    } else {
+        Vec::new()
+    };
+    let reserved_for = |action| {
+        if SHORTCUT_ACTIONS.contains(&action) {
+            &[][..]
+        } else {
+            reserved.as_slice()
+        }
+    };
+    // The keyboard and mouse are shared, but every player only uses the buttons and stick of their own gamepads
+    let pressed_by =
+        |action, player| bindings.pressed_by(action, &devices, player, reserved_for(action));
+    let just_pressed_by =
+        |action, player| bindings.just_pressed_by(action, &devices, player, reserved_for(action));
+    let pressed = |action| pressed_by(action, 0);
+    let just_pressed = |action| just_pressed_by(action, 0);
+    let stick = |player| apply_deadzone(devices.left_stick(player), input_settings.stick_deadzone);
+    player_input.stick = stick(0);
+    player_input.jump = just_pressed(Action::Jump);
+    player_input.up = pressed(Action::Jump);
+    player_input.crouch = just_pressed(Action::Crouch);
+    player_input.down = pressed(Action::Crouch);
+    player_input.dash = just_pressed(Action::Dash);
+    player_input.left = pressed(Action::Left);
+    player_input.right = pressed(Action::Right);
+    player_input.change_mode = just_pressed(Action::ChangeMode);
+    player_input.play_from_here = just_pressed(Action::PlayFromHere);
+    player_input.toggle_grid = just_pressed(Action::ToggleGrid);
+    player_input.zoom_to_fit = just_pressed(Action::ZoomToFit);
+    player_input.cycle_layer = just_pressed(Action::CycleLayer);
+    player_input.cycle_tool = just_pressed(Action::CycleTool);
+    player_input.cycle_brush = just_pressed(Action::CycleBrush);
+    player_input.toggle_mirror = just_pressed(Action::ToggleMirror);
+    player_input.delete = just_pressed(Action::Delete);
+    player_input.flip = just_pressed(Action::Flip);
+    player_input.scale_up = just_pressed(Action::ScaleUp);
+    player_input.scale_down = just_pressed(Action::ScaleDown);
+    player_input.raise = just_pressed(Action::Raise);
+    player_input.lower = just_pressed(Action::Lower);
+    player_input.next_prop = just_pressed(Action::NextProp);
+    player_input.previous_prop = just_pressed(Action::PreviousProp);
+    player_input.left_clicked = just_pressed(Action::Place);
+    player_input.left_click_held = pressed(Action::Place);
+    player_input.right_clicked = just_pressed(Action::Select);
+    player_input.right_click_held = pressed(Action::Select);
+    player_input.middle_click_held = pressed(Action::Pan);
+    player_input.ctrl = pressed(Action::Modifier);
+    player_input.save = player_input.ctrl && just_pressed(Action::Save);
+    player_input.undo = player_input.ctrl && just_pressed(Action::Undo);
+    player_input.replace = just_pressed(Action::Replace);
+    player_input.carve = just_pressed(Action::Carve);
+    player_input.confirm = just_pressed(Action::Confirm);
+    player_input.deny = just_pressed(Action::Deny);
+    player_input.cancel = just_pressed(Action::Cancel);
+    player_input.open_characters = just_pressed(Action::OpenCharacters);
+    player_input.toggle_coop = just_pressed(Action::ToggleCoop);
+    player_input.cycle_screen_layout = just_pressed(Action::CycleScreenLayout);
+    player_input.palette_slot = PALETTE_ACTIONS
+        .iter()
+        .position(|action| just_pressed(*action));
+    player_input.scroll = mouse_wheel
+        .read()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.y,
+            MouseScrollUnit::Pixel => event.y / PIXELS_PER_SCROLL_LINE,
+        })
+        .sum();
+    player_inputs.0[0] = *player_input;
+    player_inputs.0[1] = PlayerInput {
+        stick: stick(1),
+        jump: just_pressed_by(Action::Player2Jump, 1),
+        up: pressed_by(Action::Player2Jump, 1),
+        crouch: just_pressed_by(Action::Player2Crouch, 1),
+        down: pressed_by(Action::Player2Crouch, 1),
+        dash: just_pressed_by(Action::Player2Dash, 1),
+        left: pressed_by(Action::Player2Left, 1),
+        right: pressed_by(Action::Player2Right, 1),
+        ..default()
+    };
+}
+
+/// Run condition that is true while a control key is held
+pub fn ctrl_held(input: Res<PlayerInput>) -> bool {
+    input.ctrl_held()
 }
 
 /// The Resource holding all relevant input information
@@ -48,11 +152,57 @@ pub struct PlayerInput {
     down: bool,
     jump: bool,
     crouch: bool,
+    dash: bool,
     change_mode: bool,
+    play_from_here: bool,
+    toggle_grid: bool,
+    zoom_to_fit: bool,
+    cycle_layer: bool,
+    cycle_tool: bool,
+    cycle_brush: bool,
+    toggle_mirror: bool,
+    delete: bool,
+    flip: bool,
+    scale_up: bool,
+    scale_down: bool,
+    raise: bool,
+    lower: bool,
+    next_prop: bool,
+    previous_prop: bool,
+    middle_click_held: bool,
+    ctrl: bool,
     right_clicked: bool,
     right_click_held: bool,
     left_clicked: bool,
     left_click_held: bool,
+    palette_slot: Option<usize>,
+    /// The position of the left gamepad stick, after applying the deadzone
+    stick: Vec2,
+    scroll: f32,
+    save: bool,
+    undo: bool,
+    replace: bool,
+    carve: bool,
+    confirm: bool,
+    deny: bool,
+    cancel: bool,
+    open_characters: bool,
+    toggle_coop: bool,
+    cycle_screen_layout: bool,
+}
+
+/// The Resource holding the input every player moves with, indexed by their [PlayerNumber](crate::player::PlayerNumber)
+///
+/// The first player moves with the same input as [PlayerInput], the second one only with the actions meant for them.
+/// Every player reads the gamepad buttons and stick of their own gamepads, see [InputDevices::player_of]
+#[derive(Resource, Default)]
+pub struct PlayerInputs(pub [PlayerInput; MAX_PLAYERS]);
+
+impl PlayerInputs {
+    /// Returns the input of the given player
+    pub fn get(&self, player: usize) -> PlayerInput {
+        self.0.get(player).copied().unwrap_or_default()
+    }
 }
 
 #[allow(unused)]
@@ -61,10 +211,25 @@ impl PlayerInput {
         self.jump
     }
 
+    /// Returns whether jump is being held down
+    pub fn jump_held(self) -> bool {
+        self.up
+    }
+
     pub fn crouch_pressed(self) -> bool {
         self.crouch
     }
 
+    /// Returns whether crouch is being held down
+    pub fn crouch_held(self) -> bool {
+        self.down
+    }
+
+    pub fn dash_pressed(self) -> bool {
+        self.dash
+    }
+
+    /// Returns the horizontal movement direction. Its length is below 1 while a gamepad stick is tilted only partially
     pub fn direction_vector(self) -> Vec2 {
         let mut out = Vec2::ZERO;
         if self.left {
@@ -73,6 +238,9 @@ impl PlayerInput {
         if self.right {
             out += Vec2::new(1., 0.);
         }
+        if out == Vec2::ZERO {
+            out.x = self.stick.x;
+        }
         out
     }
 
@@ -90,6 +258,9 @@ impl PlayerInput {
         if self.down {
             out += Vec2::new(0., -1.)
         }
+        if out == Vec2::ZERO {
+            out = self.stick;
+        }
         out
     }
 
@@ -97,11 +268,141 @@ impl PlayerInput {
         self.change_mode
     }
 
+    pub fn play_from_here_pressed(self) -> bool {
+        self.play_from_here
+    }
+
+    pub fn toggle_grid_pressed(self) -> bool {
+        self.toggle_grid
+    }
+
+    pub fn zoom_to_fit_pressed(self) -> bool {
+        self.zoom_to_fit
+    }
+
+    pub fn cycle_layer_pressed(self) -> bool {
+        self.cycle_layer
+    }
+
+    pub fn cycle_tool_pressed(self) -> bool {
+        self.cycle_tool
+    }
+
+    pub fn cycle_brush_pressed(self) -> bool {
+        self.cycle_brush
+    }
+
+    pub fn toggle_mirror_pressed(self) -> bool {
+        self.toggle_mirror
+    }
+
+    pub fn delete_pressed(self) -> bool {
+        self.delete
+    }
+
+    pub fn flip_pressed(self) -> bool {
+        self.flip
+    }
+
+    pub fn scale_up_pressed(self) -> bool {
+        self.scale_up
+    }
+
+    pub fn scale_down_pressed(self) -> bool {
+        self.scale_down
+    }
+
+    /// Returns whether the selection should be moved in front of other things
+    pub fn raise_pressed(self) -> bool {
+        self.raise
+    }
+
+    /// Returns whether the selection should be moved behind other things
+    pub fn lower_pressed(self) -> bool {
+        self.lower
+    }
+
+    pub fn next_prop_pressed(self) -> bool {
+        self.next_prop
+    }
+
+    pub fn previous_prop_pressed(self) -> bool {
+        self.previous_prop
+    }
+
+    pub fn middle_click_held(self) -> bool {
+        self.middle_click_held
+    }
+
+    pub fn ctrl_held(self) -> bool {
+        self.ctrl
+    }
+
     pub fn right_clicked(self) -> bool {
         self.right_clicked
     }
 
+    pub fn right_click_held(self) -> bool {
+        self.right_click_held
+    }
+
     pub fn left_clicked(self) -> bool {
         self.left_clicked
     }
+
+    pub fn left_click_held(self) -> bool {
+        self.left_click_held
+    }
+
+    pub fn save_pressed(self) -> bool {
+        self.save
+    }
+
+    pub fn undo_pressed(self) -> bool {
+        self.undo
+    }
+
+    /// Returns whether the hovered material should be replaced with the selected one
+    pub fn replace_pressed(self) -> bool {
+        self.replace
+    }
+
+    /// Returns whether caves should be carved into the selected region
+    pub fn carve_pressed(self) -> bool {
+        self.carve
+    }
+
+    pub fn confirm_pressed(self) -> bool {
+        self.confirm
+    }
+
+    pub fn deny_pressed(self) -> bool {
+        self.deny
+    }
+
+    pub fn cancel_pressed(self) -> bool {
+        self.cancel
+    }
+
+    pub fn open_characters_pressed(self) -> bool {
+        self.open_characters
+    }
+
+    pub fn toggle_coop_pressed(self) -> bool {
+        self.toggle_coop
+    }
+
+    pub fn cycle_screen_layout_pressed(self) -> bool {
+        self.cycle_screen_layout
+    }
+
+    /// Returns the palette slot whose number key was just pressed
+    pub fn palette_slot_pressed(self) -> Option<usize> {
+        self.palette_slot
+    }
+
+    /// Returns how far the mouse wheel was scrolled this frame. Positive values scroll up
+    pub fn scroll(self) -> f32 {
+        self.scroll
+    }
 }
@@ -0,0 +1,279 @@
+use bevy::prelude::*;
+use strum::IntoEnumIterator;
+
+use super::{
+    bindings::{Action, Binding, Bindings, InputDevices},
+    handle_input,
+};
+use crate::{config::SaveSettings, settings::update_settings_file};
+
+/// The Plugin containing the screen that lets players change their key bindings
+pub struct RebindingPlugin;
+
+impl Plugin for RebindingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RebindingScreen>().add_systems(
+            Update,
+            (toggle_screen, capture_binding, press_buttons, update_labels)
+                .chain()
+                .before(handle_input),
+        );
+    }
+}
+
+/// The Resource keeping track of the rebinding screen
+#[derive(Resource, Default)]
+pub struct RebindingScreen {
+    open: bool,
+    /// The [Action] waiting for the next key or mouse button to be pressed
+    capturing: Option<Action>,
+}
+
+impl RebindingScreen {
+    /// Returns whether the rebinding screen is shown, during which no other input is handled
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+}
+
+/// Marks the root node of the rebinding screen
+#[derive(Component)]
+struct RebindingScreenMarker;
+
+/// A button that captures a new binding for its [Action]
+#[derive(Component, Clone, Copy)]
+struct ActionButton(Action);
+
+/// The button that restores the default bindings
+#[derive(Component)]
+struct ResetButton;
+
+/// Helper function that writes the bindings to the settings file
+fn save_bindings(bindings: &Bindings, save_settings: &SaveSettings) {
+    if let Err(e) = update_settings_file(&save_settings.settings_path, |settings| {
+        settings.bindings = Some(bindings.clone());
+    }) {
+        eprintln!("Failed to save key bindings: {}", e);
+    }
+}
+
+/// Helper function that creates a button with a label
+fn button(parent: &mut ChildBuilder, width: f32, component: impl Bundle) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(width),
+                    padding: UiRect::all(Val::Px(4.)),
+                    ..default()
+                },
+                background_color: Color::srgba(1., 1., 1., 0.1).into(),
+                ..default()
+            },
+            component,
+        ))
+        .with_children(|button| {
+            button.spawn(TextBundle::from_section(
+                "",
+                TextStyle {
+                    font_size: 16.,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ));
+        });
+}
+
+/// Helper function that spawns the rebinding screen, with a button per [Action]
+fn spawn_screen(commands: &mut Commands, bindings: &Bindings) {
+    let cancel = bindings
+        .get(Action::Cancel)
+        .iter()
+        .map(Binding::to_string)
+        .collect::<Vec<String>>()
+        .join(" or ");
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    padding: UiRect::all(Val::Px(20.)),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(10.),
+                    ..default()
+                },
+                background_color: Color::srgba(0., 0., 0., 0.85).into(),
+                z_index: ZIndex::Global(50),
+                ..default()
+            },
+            Interaction::default(),
+            RebindingScreenMarker,
+            Name::new("Rebinding Screen"),
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                format!(
+                    "Key Bindings - click an action, then press a key or mouse button to add it, or one of its bindings to remove it. {} cancels.",
+                    cancel
+                ),
+                TextStyle {
+                    font_size: 20.,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ));
+            button(parent, 200., ResetButton);
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        flex_wrap: FlexWrap::Wrap,
+                        align_content: AlignContent::FlexStart,
+                        flex_grow: 1.,
+                        row_gap: Val::Px(4.),
+                        column_gap: Val::Px(4.),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|list| {
+                    Action::iter().for_each(|action| {
+                        button(list, 320., ActionButton(action));
+                    });
+                });
+        });
+}
+
+/// This System opens and closes the rebinding screen
+fn toggle_screen(
+    mut commands: Commands,
+    devices: InputDevices,
+    bindings: Res<Bindings>,
+    mut screen: ResMut<RebindingScreen>,
+    query: Query<Entity, With<RebindingScreenMarker>>,
+) {
+    if screen.capturing.is_some() {
+        return;
+    }
+    let toggle = bindings.just_pressed(Action::OpenBindings, &devices);
+    let close = screen.open && bindings.just_pressed(Action::Cancel, &devices);
+    if !toggle && !close {
+        return;
+    }
+    screen.open = !screen.open;
+    if screen.open {
+        spawn_screen(&mut commands, &bindings);
+    } else {
+        query.iter().for_each(|entity| {
+            commands.entity(entity).despawn_recursive();
+        });
+    }
+}
+
+/// This System adds the next pressed key or mouse button to the bindings of the [Action] waiting for it,
+/// or removes it if the action is already bound to it
+fn capture_binding(
+    devices: InputDevices,
+    save_settings: Res<SaveSettings>,
+    mut bindings: ResMut<Bindings>,
+    mut screen: ResMut<RebindingScreen>,
+) {
+    let Some(action) = screen.capturing else {
+        return;
+    };
+    if bindings.just_pressed(Action::Cancel, &devices) {
+        screen.capturing = None;
+        return;
+    }
+    if let Some(binding) = devices.first_just_pressed() {
+        let action_bindings = bindings.0.entry(action).or_default();
+        match action_bindings.iter().position(|bound| *bound == binding) {
+            Some(index) => {
+                action_bindings.remove(index);
+            }
+            None => action_bindings.push(binding),
+        }
+        save_bindings(&bindings, &save_settings);
+        screen.capturing = None;
+    }
+}
+
+/// This System starts capturing a binding, or restores the defaults, when a button is pressed.
+/// It runs after capturing, so the click on the button is not captured itself
+fn press_buttons(
+    save_settings: Res<SaveSettings>,
+    action_buttons: Query<(&Interaction, &ActionButton), Changed<Interaction>>,
+    reset_buttons: Query<&Interaction, (Changed<Interaction>, With<ResetButton>)>,
+    mut bindings: ResMut<Bindings>,
+    mut screen: ResMut<RebindingScreen>,
+) {
+    if !screen.open {
+        return;
+    }
+    action_buttons.iter().for_each(|(interaction, button)| {
+        if *interaction == Interaction::Pressed {
+            screen.capturing = Some(button.0);
+        }
+    });
+    if reset_buttons
+        .iter()
+        .any(|interaction| *interaction == Interaction::Pressed)
+    {
+        *bindings = Bindings::default();
+        save_bindings(&bindings, &save_settings);
+        screen.capturing = None;
+    }
+}
+
+/// This System shows the current bindings on the buttons
+fn update_labels(
+    bindings: Res<Bindings>,
+    screen: Res<RebindingScreen>,
+    action_buttons: Query<(&ActionButton, &Children)>,
+    reset_buttons: Query<&Children, With<ResetButton>>,
+    added: Query<(), Added<ActionButton>>,
+    mut texts: Query<&mut Text>,
+) {
+    if !bindings.is_changed() && !screen.is_changed() && added.is_empty() {
+        return;
+    }
+    let mut set_label = |children: &Children, label: String| {
+        children.iter().for_each(|&child| {
+            if let Ok(mut text) = texts.get_mut(child) {
+                text.sections[0].value.clone_from(&label);
+            }
+        });
+    };
+    action_buttons.iter().for_each(|(button, children)| {
+        let bound = if screen.capturing == Some(button.0) {
+            "press a key to add or remove...".to_string()
+        } else {
+            bindings
+                .get(button.0)
+                .iter()
+                .map(Binding::to_string)
+                .collect::<Vec<String>>()
+                .join(", ")
+        };
+        let conflicts = bindings.conflicts(button.0);
+        let conflicts = if conflicts.is_empty() {
+            String::new()
+        } else {
+            let names = conflicts
+                .iter()
+                .map(|action| action.as_ref())
+                .collect::<Vec<&str>>()
+                .join(", ");
+            format!(" (conflicts with {})", names)
+        };
+        set_label(
+            children,
+            format!("{}: {}{}", button.0.as_ref(), bound, conflicts),
+        );
+    });
+    reset_buttons.iter().for_each(|children| {
+        set_label(children, "Reset to Defaults".to_string());
+    });
+}
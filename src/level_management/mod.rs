@@ -1,31 +1,94 @@
-use crate::states::editing::{HoveringBlock, SerdeMapContainer};
+pub mod props;
+pub mod saving;
+
+use crate::config::{LevelSettings, SaveSettings};
+use crate::states::editing::{HoveringBlock, LayerMap, SerdeMapContainer};
 use crate::utilities::assets::Material;
 use crate::utilities::assets::{init, Column, ImageHandles, Row};
 use bevy::prelude::*;
 use bevy_rapier2d::geometry::Collider;
+use props::{execute_prop_queues, LevelProps};
+use saving::SavingPlugin;
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
-use std::fs::read_to_string;
+use std::fs::{read_to_string, write};
+use std::io;
+use strum::IntoEnumIterator;
+use strum_macros::{AsRefStr, EnumIter};
 
 /// The Plugin containing everything related to the [Level]
 pub struct LevelManagementPlugin;
 
 impl Plugin for LevelManagementPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(Level {
-            material_map: HashMap::new(),
-            entity_map: HashMap::new(),
-            level_spawn_queue: HashSet::new(),
-            level_despawn_queue: HashSet::new(),
-            texture_update_queue: HashSet::new(),
-        })
-        .add_systems(Startup, load_level.after(init))
-        .add_systems(Update, execute_level_queues);
+        app.insert_resource(Level::default())
+            .add_systems(Startup, load_level.after(init))
+            .add_systems(Update, execute_level_queues)
+            .add_plugins(SavingPlugin);
+    }
+}
+
+/// The layers a level is made of, from back to front
+#[derive(
+    Component,
+    Hash,
+    Ord,
+    PartialOrd,
+    PartialEq,
+    Eq,
+    Copy,
+    Clone,
+    Default,
+    EnumIter,
+    AsRefStr,
+    Debug,
+    Serialize,
+    Deserialize,
+)]
+pub enum Layer {
+    Background,
+    #[default]
+    Solid,
+    Foreground,
+}
+
+impl Layer {
+    /// Returns the z coordinate the blocks of this layer are drawn at
+    pub fn z(&self) -> f32 {
+        match self {
+            Layer::Background => -5.,
+            Layer::Solid => 0.,
+            Layer::Foreground => 20.,
+        }
+    }
+
+    /// Returns whether the blocks of this layer collide with the player
+    pub fn is_collidable(&self) -> bool {
+        *self == Layer::Solid
     }
 }
 
 /// A Resource containing all runtime data for the Level
 #[derive(Resource, Debug)]
 pub struct Level {
+    pub layers: HashMap<Layer, LevelLayer>,
+    pub props: LevelProps,
+}
+
+impl Default for Level {
+    fn default() -> Self {
+        Level {
+            layers: Layer::iter()
+                .map(|layer| (layer, LevelLayer::default()))
+                .collect(),
+            props: LevelProps::default(),
+        }
+    }
+}
+
+/// The runtime data of a single [Layer]
+#[derive(Debug, Default)]
+pub struct LevelLayer {
     pub material_map: HashMap<(i32, i32), Material>,
     pub entity_map: HashMap<(i32, i32), Entity>,
     pub level_spawn_queue: HashSet<(i32, i32)>,
@@ -33,23 +96,106 @@ pub struct Level {
     pub texture_update_queue: HashSet<(i32, i32)>,
 }
 
+/// A change of a single block, as recorded for undoing it
+#[derive(Debug, Clone, Copy)]
+pub struct BlockChange {
+    pub layer: Layer,
+    pub position: (i32, i32),
+    pub before: Option<Material>,
+    pub after: Option<Material>,
+}
+
 /// Helper function to load the level from its file
-fn load_level(mut level: ResMut<Level>, mut hovering_block: ResMut<HoveringBlock>) {
-    let json = read_to_string("assets/level.json");
-    if !json.is_err() {
-        let deserialized_option = serde_json::from_str(&json.unwrap());
-        if !deserialized_option.is_err() {
-            let container: SerdeMapContainer = deserialized_option.unwrap();
-            level.material_map = container.map;
-        }
+pub fn load_level(
+    mut level: ResMut<Level>,
+    mut hovering_block: ResMut<HoveringBlock>,
+    mut ls: ResMut<LevelSettings>,
+    save_settings: Res<SaveSettings>,
+) {
+    if let Some(container) = read_level_file(&save_settings.level_path) {
+        apply_level_file(container, &mut level, &mut ls);
     }
-    hovering_block.original_material = match level.material_map.get(&hovering_block.last_hovered) {
-        Some(material) => Some(*material),
-        None => None,
-    };
-    for (pos, material) in level.material_map.clone() {
-        level.insert(pos, Some(material));
+    hovering_block.original_material = level
+        .layer(hovering_block.layer)
+        .material_map
+        .get(&hovering_block.last_hovered)
+        .copied();
+}
+
+/// Reads the level file at the given path. Returns `None` if it is missing or invalid
+pub fn read_level_file(path: &str) -> Option<SerdeMapContainer> {
+    let json = read_to_string(path).ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+/// Replaces the current level with the contents of a level file
+pub fn apply_level_file(
+    mut container: SerdeMapContainer,
+    level: &mut Level,
+    ls: &mut LevelSettings,
+) {
+    Layer::iter().for_each(|layer| {
+        let map = match layer {
+            Layer::Solid => std::mem::take(&mut container.map),
+            _ => container
+                .layers
+                .remove(&layer)
+                .map(|layer_map| layer_map.0)
+                .unwrap_or_default(),
+        };
+        level.layer_mut(layer).load_map(map);
+    });
+    level.props.load(std::mem::take(&mut container.props));
+    if let Some((x, y)) = container.spawn_location {
+        ls.spawn_location.translation.x = x;
+        ls.spawn_location.translation.y = y;
+    }
+    if let Some(death_height) = container.death_height {
+        ls.death_height = death_height;
     }
+    if let Some(dash_cooldown) = container.dash_cooldown {
+        ls.dash_cooldown = dash_cooldown;
+    }
+}
+
+/// Writes the level to the file at the given path, leaving out the editors hover preview
+pub fn write_level_file(
+    path: &str,
+    level: &Level,
+    hovering_block: &HoveringBlock,
+    ls: &LevelSettings,
+) -> io::Result<()> {
+    let mut maps: HashMap<Layer, HashMap<(i32, i32), Material>> = level
+        .layers
+        .iter()
+        .map(|(&layer, level_layer)| (layer, level_layer.material_map.clone()))
+        .collect();
+    let map = maps.get_mut(&hovering_block.layer).unwrap();
+    hovering_block
+        .preview
+        .iter()
+        .for_each(|(&position, &original)| {
+            match original {
+                Some(material) => map.insert(position, material),
+                None => map.remove(&position),
+            };
+        });
+    let container = SerdeMapContainer {
+        map: maps.remove(&Layer::Solid).unwrap_or_default(),
+        layers: maps
+            .into_iter()
+            .map(|(layer, map)| (layer, LayerMap(map)))
+            .collect(),
+        props: level.props.to_vec(),
+        spawn_location: Some((
+            ls.spawn_location.translation.x,
+            ls.spawn_location.translation.y,
+        )),
+        death_height: Some(ls.death_height),
+        dash_cooldown: Some(ls.dash_cooldown),
+    };
+    let serialized = serde_json::to_string(&container).map_err(io::Error::other)?;
+    write(path, serialized)
 }
 
 /// System that executes all deferred actions related to the [Level]
@@ -57,6 +203,20 @@ pub fn execute_level_queues(
     mut commands: Commands,
     mut level: ResMut<Level>,
     image_handles: Res<ImageHandles>,
+    asset_server: Res<AssetServer>,
+) {
+    level.layers.iter_mut().for_each(|(&layer, level_layer)| {
+        execute_layer_queues(&mut commands, layer, level_layer, &image_handles);
+    });
+    execute_prop_queues(&mut commands, &mut level.props, &asset_server);
+}
+
+/// Helper function that executes the deferred actions of a single [Layer]
+fn execute_layer_queues(
+    commands: &mut Commands,
+    layer: Layer,
+    level: &mut LevelLayer,
+    image_handles: &ImageHandles,
 ) {
     level
         .level_despawn_queue
@@ -87,15 +247,18 @@ pub fn execute_level_queues(
                     .get(&(material.unwrap().clone(), row, column))
                     .unwrap()
                     .clone();
-                let entity = commands.spawn((
+                let mut entity = commands.spawn((
                     SpriteBundle {
                         texture,
-                        transform: Transform::from_translation(world_position.extend(0.)),
+                        transform: Transform::from_translation(world_position.extend(layer.z())),
                         ..default()
                     },
-                    Collider::cuboid(16. / 2., 16. / 2.),
+                    layer,
                     Name::new("Textured Block"),
                 ));
+                if layer.is_collidable() {
+                    entity.insert(Collider::cuboid(16. / 2., 16. / 2.));
+                }
                 level.entity_map.insert(position, entity.id());
             }
             level.level_spawn_queue.remove(&position);
@@ -124,6 +287,85 @@ pub fn execute_level_queues(
 }
 
 impl Level {
+    /// Returns the given [Layer]
+    pub fn layer(&self, layer: Layer) -> &LevelLayer {
+        self.layers.get(&layer).unwrap()
+    }
+
+    /// Returns the given [Layer] mutably
+    pub fn layer_mut(&mut self, layer: Layer) -> &mut LevelLayer {
+        self.layers.get_mut(&layer).unwrap()
+    }
+
+    /// Returns the area covered by blocks of any layer in world coordinates, or `None` if the level is empty
+    pub fn bounds(&self) -> Option<Rect> {
+        self.layers
+            .values()
+            .filter_map(LevelLayer::bounds)
+            .reduce(|bounds, layer_bounds| bounds.union(layer_bounds))
+    }
+
+    /// Replaces every block of one material with another in all layers, optionally only inside a region of grid cells.
+    /// Replacing with `None` removes the blocks. Returns the changes that were made
+    pub fn replace_material(
+        &mut self,
+        from: Material,
+        to: Option<Material>,
+        region: Option<IRect>,
+    ) -> Vec<BlockChange> {
+        if to == Some(from) {
+            return Vec::new();
+        }
+        let changes: Vec<BlockChange> = self
+            .layers
+            .iter()
+            .flat_map(|(&layer, level_layer)| {
+                level_layer
+                    .material_map
+                    .iter()
+                    .filter(move |(&(x, y), &material)| {
+                        let inside = match region {
+                            Some(region) => region.contains(IVec2::new(x, y)),
+                            None => true,
+                        };
+                        material == from && inside
+                    })
+                    .map(move |(&position, _)| BlockChange {
+                        layer,
+                        position,
+                        before: Some(from),
+                        after: to,
+                    })
+            })
+            .collect();
+        self.apply_changes(&changes, false);
+        changes
+    }
+
+    /// Applies the given changes in one batch. If `reverse` is set, they are undone instead
+    pub fn apply_changes(&mut self, changes: &[BlockChange], reverse: bool) {
+        let ordered: Box<dyn Iterator<Item = &BlockChange>> = if reverse {
+            Box::new(changes.iter().rev())
+        } else {
+            Box::new(changes.iter())
+        };
+        ordered.for_each(|change| {
+            let material = if reverse { change.before } else { change.after };
+            self.layer_mut(change.layer)
+                .set_block(change.position, material);
+        });
+    }
+
+    /// Returns the number of blocks in all layers
+    pub fn block_count(&self) -> usize {
+        self.layers
+            .values()
+            .map(|level_layer| level_layer.material_map.len())
+            .sum()
+    }
+}
+
+impl LevelLayer {
     /// Adds or changes the Material at the given location
     pub fn insert(&mut self, position: (i32, i32), material: Option<Material>) {
         if material.is_none() {
@@ -132,34 +374,97 @@ impl Level {
         }
         self.material_map
             .insert(position, material.unwrap().clone());
+        // A block removed and placed again within the same frame keeps its entity
+        self.level_despawn_queue.remove(&position);
         self.texture_update_queue.insert(position);
         if self.entity_map.get(&position.clone()).is_none() {
             self.level_spawn_queue.insert(position);
         }
     }
 
+    /// Adds, changes or removes the Material at the given location and retextures the blocks around it to match.
+    /// Small materials alternate their textures, so the blocks below and to the right are retextured up to the next gap
+    pub fn set_block(&mut self, (x, y): (i32, i32), material: Option<Material>) {
+        self.insert((x, y), material);
+        let below = (1..).map(|offset| (x, y - offset));
+        let right = (1..).map(|offset| (x + offset, y));
+        let affected: Vec<(i32, i32)> = [(x - 1, y), (x, y + 1)]
+            .into_iter()
+            .filter(|position| self.material_map.contains_key(position))
+            .chain(below.take_while(|position| self.material_map.contains_key(position)))
+            .chain(right.take_while(|position| self.material_map.contains_key(position)))
+            .collect();
+        self.texture_update_queue.extend(affected);
+    }
+
+    /// Replaces every block of the layer with the given map
+    pub fn load_map(&mut self, map: HashMap<(i32, i32), Material>) {
+        let removed: Vec<(i32, i32)> = self
+            .material_map
+            .keys()
+            .filter(|position| !map.contains_key(position))
+            .copied()
+            .collect();
+        removed
+            .into_iter()
+            .for_each(|position| self.remove(position));
+        map.into_iter()
+            .for_each(|(position, material)| self.insert(position, Some(material)));
+    }
+
+    /// Returns the area covered by blocks in world coordinates, or `None` if the layer is empty
+    pub fn bounds(&self) -> Option<Rect> {
+        let mut positions = self.material_map.keys().map(|&(x, y)| IVec2::new(x, y));
+        let first = positions.next()?;
+        let (min, max) = positions.fold((first, first), |(min, max), position| {
+            (min.min(position), max.max(position))
+        });
+        Some(Rect::from_corners(
+            min.as_vec2() * 16.,
+            (max + IVec2::ONE).as_vec2() * 16.,
+        ))
+    }
+
     /// Removes the Material at the given location
     pub fn remove(&mut self, (x, y): (i32, i32)) {
         self.material_map.remove(&(x, y));
+        self.level_spawn_queue.remove(&(x, y));
         self.level_despawn_queue.insert((x, y));
     }
 
     /// Helper function for grid
-    pub fn get_row(&self, (x, y): (i32, i32)) -> Row {
-        if self.material_map.get(&(x, y)).is_none() {
-            return Row::TOP;
-        }
-        let material = self.material_map.get(&(x, y)).unwrap();
-        let mut top = self.material_map.get(&(x, y + 1)).is_some();
-        if top {
-            let top_material = *self.material_map.get(&(x, y + 1)).unwrap();
-            top = &top_material == material;
-        }
-        let mut bottom = self.material_map.get(&(x, y - 1)).is_some();
-        if bottom {
-            let bottom_material = *self.material_map.get(&(x, y - 1)).unwrap();
-            bottom = &bottom_material == material;
+    pub fn get_row(&self, position: (i32, i32)) -> Row {
+        self.get_row_with(position, &HashMap::new())
+    }
+
+    /// Helper function for grid
+    pub fn get_column(&self, position: (i32, i32)) -> Column {
+        self.get_column_with(position, &HashMap::new())
+    }
+
+    /// Returns the material at the given location, as if the given cells held the given materials instead
+    fn material_with(
+        &self,
+        position: (i32, i32),
+        overrides: &HashMap<(i32, i32), Option<Material>>,
+    ) -> Option<Material> {
+        match overrides.get(&position) {
+            Some(material) => *material,
+            None => self.material_map.get(&position).copied(),
         }
+    }
+
+    /// Returns the row of the texture at the given location, as if the given cells held the given materials instead
+    pub fn get_row_with(
+        &self,
+        (x, y): (i32, i32),
+        overrides: &HashMap<(i32, i32), Option<Material>>,
+    ) -> Row {
+        let Some(material) = self.material_with((x, y), overrides) else {
+            return Row::TOP;
+        };
+        let top = self.material_with((x, y + 1), overrides) == Some(material);
+        let bottom = self.material_with((x, y - 1), overrides) == Some(material);
         let row = match (top, bottom) {
             (false, false) => Row::CENTER,
             (true, false) => Row::BOTTOM,
@@ -169,29 +474,24 @@ impl Level {
         if !material.is_small() || row == Row::TOP {
             return row;
         }
-        return match self.get_row((x, y + 1)) {
+        return match self.get_row_with((x, y + 1), overrides) {
             Row::TOP => Row::BOTTOM,
             Row::BOTTOM => Row::TOP,
             Row::CENTER => Row::BOTTOM,
         };
     }
 
-    /// Helper function for grid
-    pub fn get_column(&self, (x, y): (i32, i32)) -> Column {
-        if self.material_map.get(&(x, y)).is_none() {
+    /// Returns the column of the texture at the given location, as if the given cells held the given materials instead
+    pub fn get_column_with(
+        &self,
+        (x, y): (i32, i32),
+        overrides: &HashMap<(i32, i32), Option<Material>>,
+    ) -> Column {
+        let Some(material) = self.material_with((x, y), overrides) else {
             return Column::LEFT;
-        }
-        let material = self.material_map.get(&(x, y)).unwrap();
-        let mut left = self.material_map.get(&(x - 1, y)).is_some();
-        if left {
-            let left_material = *self.material_map.get(&(x - 1, y)).unwrap();
-            left = &left_material == material;
-        }
-        let mut right = self.material_map.get(&(x + 1, y)).is_some();
-        if right {
-            let right_material = *self.material_map.get(&(x + 1, y)).unwrap();
-            right = &right_material == material;
-        }
+        };
+        let left = self.material_with((x - 1, y), overrides) == Some(material);
+        let right = self.material_with((x + 1, y), overrides) == Some(material);
         let column = match (left, right) {
             (false, false) => Column::MIDDLE,
             (true, false) => Column::RIGHT,
@@ -201,7 +501,7 @@ impl Level {
         if !material.is_small() || column == Column::LEFT {
             return column;
         }
-        return match self.get_column((x - 1, y)) {
+        return match self.get_column_with((x - 1, y), overrides) {
             Column::LEFT => Column::RIGHT,
             Column::RIGHT => Column::LEFT,
             Column::MIDDLE => Column::RIGHT,
@@ -0,0 +1,164 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// A decorative sprite that is placed freely in the world instead of on the block grid
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Prop {
+    /// The path of the image, relative to the assets folder
+    pub image: String,
+    pub position: (f32, f32),
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+    #[serde(default)]
+    pub flip_x: bool,
+    /// The z coordinate the prop is drawn at, which decides what it is drawn in front of
+    #[serde(default = "default_z")]
+    pub z: f32,
+}
+
+/// Helper function for serde, so props without a scale are drawn at their original size
+fn default_scale() -> f32 {
+    1.
+}
+
+/// Helper function for serde, so props without a z coordinate are drawn in front of solid blocks
+fn default_z() -> f32 {
+    1.
+}
+
+impl Prop {
+    /// Creates a prop with the given image at the given position
+    pub fn new(image: String, position: Vec2) -> Self {
+        Prop {
+            image,
+            position: (position.x, position.y),
+            scale: default_scale(),
+            flip_x: false,
+            z: default_z(),
+        }
+    }
+
+    /// Returns the position of the prop as a vector
+    pub fn position(&self) -> Vec2 {
+        Vec2::new(self.position.0, self.position.1)
+    }
+
+    /// Returns the transform the entity of the prop should have
+    pub fn transform(&self) -> Transform {
+        Transform::from_translation(self.position().extend(self.z))
+            .with_scale(Vec3::new(self.scale, self.scale, 1.))
+    }
+}
+
+/// Marks the entity of a [Prop]
+#[derive(Component)]
+pub struct PropMarker;
+
+/// The runtime data of all props in the level
+#[derive(Debug, Default)]
+pub struct LevelProps {
+    pub props: HashMap<u32, Prop>,
+    pub entity_map: HashMap<u32, Entity>,
+    pub spawn_queue: HashSet<u32>,
+    pub despawn_queue: HashSet<u32>,
+    pub update_queue: HashSet<u32>,
+    next_id: u32,
+}
+
+impl LevelProps {
+    /// Adds a prop to the level and returns its id
+    pub fn insert(&mut self, prop: Prop) -> u32 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.props.insert(id, prop);
+        self.spawn_queue.insert(id);
+        id
+    }
+
+    /// Returns the prop with the given id
+    pub fn get(&self, id: u32) -> Option<&Prop> {
+        self.props.get(&id)
+    }
+
+    /// Changes the prop with the given id. Changes to its image only apply once it is respawned
+    pub fn update(&mut self, id: u32, prop: Prop) {
+        if let Some(existing) = self.props.get_mut(&id) {
+            *existing = prop;
+            self.update_queue.insert(id);
+        }
+    }
+
+    /// Removes the prop with the given id
+    pub fn remove(&mut self, id: u32) {
+        self.props.remove(&id);
+        self.despawn_queue.insert(id);
+    }
+
+    /// Replaces every prop with the given ones
+    pub fn load(&mut self, props: Vec<Prop>) {
+        let ids: Vec<u32> = self.props.keys().copied().collect();
+        ids.into_iter().for_each(|id| self.remove(id));
+        props.into_iter().for_each(|prop| {
+            self.insert(prop);
+        });
+    }
+
+    /// Returns all props in the order they were placed, ready to be written to a level file
+    pub fn to_vec(&self) -> Vec<Prop> {
+        self.props
+            .iter()
+            .collect::<BTreeMap<_, _>>()
+            .into_values()
+            .cloned()
+            .collect()
+    }
+}
+
+/// Helper function that executes the deferred actions of the props
+pub fn execute_prop_queues(
+    commands: &mut Commands,
+    level_props: &mut LevelProps,
+    asset_server: &AssetServer,
+) {
+    level_props.despawn_queue.drain().for_each(|id| {
+        if let Some(entity) = level_props.entity_map.remove(&id) {
+            commands.entity(entity).despawn();
+        }
+    });
+    level_props.spawn_queue.drain().for_each(|id| {
+        let Some(prop) = level_props.props.get(&id) else {
+            return;
+        };
+        let entity = commands
+            .spawn((
+                SpriteBundle {
+                    texture: asset_server.load(prop.image.clone()),
+                    transform: prop.transform(),
+                    sprite: Sprite {
+                        flip_x: prop.flip_x,
+                        ..default()
+                    },
+                    ..default()
+                },
+                PropMarker,
+                Name::new("Prop"),
+            ))
+            .id();
+        level_props.entity_map.insert(id, entity);
+    });
+    level_props.update_queue.drain().for_each(|id| {
+        let (Some(prop), Some(&entity)) =
+            (level_props.props.get(&id), level_props.entity_map.get(&id))
+        else {
+            return;
+        };
+        commands.entity(entity).insert((
+            prop.transform(),
+            Sprite {
+                flip_x: prop.flip_x,
+                ..default()
+            },
+        ));
+    });
+}
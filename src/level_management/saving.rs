@@ -0,0 +1,345 @@
+use bevy::{prelude::*, window::WindowCloseRequested};
+use std::{fs::remove_file, path::Path};
+
+use super::{apply_level_file, load_level, read_level_file, write_level_file, Level};
+use crate::{
+    config::{LevelSettings, SaveSettings},
+    input::{handle_input, PlayerInput},
+    states::editing::HoveringBlock,
+};
+
+/// The Plugin containing everything related to saving the level
+pub struct SavingPlugin;
+
+impl Plugin for SavingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SaveState>()
+            .add_systems(
+                Startup,
+                (
+                    spawn_save_ui,
+                    setup_autosave,
+                    check_recovery_file.after(load_level),
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    save_on_command,
+                    autosave,
+                    request_quit,
+                    answer_prompt,
+                    update_save_ui,
+                )
+                    .chain()
+                    .after(handle_input),
+            );
+    }
+}
+
+/// The Resource keeping track of changes that have not been written to disk yet
+#[derive(Resource, Default)]
+pub struct SaveState {
+    dirty: bool,
+    recovery_outdated: bool,
+    /// Whether restoring the recovery file of the last session was left for later, so it must not be overwritten or removed
+    recovery_kept: bool,
+    prompt: Option<Prompt>,
+}
+
+impl SaveState {
+    /// Marks the level as modified since the last save
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+        self.recovery_outdated = true;
+    }
+
+    /// Returns whether the level was modified since the last save
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+}
+
+/// The questions that can be asked of the user
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Prompt {
+    Quit,
+    Restore,
+}
+
+/// A Resource that keeps track of when the next autosave is due
+#[derive(Resource, Deref, DerefMut)]
+struct AutosaveTimer(Timer);
+
+/// Marks the text showing that there are unsaved changes
+#[derive(Component)]
+struct UnsavedIndicatorMarker;
+
+/// Marks the text showing the current [Prompt]
+#[derive(Component)]
+struct PromptMarker;
+
+/// Helper function that saves the level and discards the recovery file, unless it was kept for later
+fn save(
+    save_state: &mut SaveState,
+    save_settings: &SaveSettings,
+    level: &Level,
+    hovering_block: &HoveringBlock,
+    ls: &LevelSettings,
+) -> bool {
+    if let Err(e) = write_level_file(&save_settings.level_path, level, hovering_block, ls) {
+        eprintln!("Failed to save level: {}", e);
+        return false;
+    }
+    save_state.dirty = false;
+    save_state.recovery_outdated = false;
+    if !save_state.recovery_kept {
+        discard_recovery_file(save_settings);
+    }
+    true
+}
+
+/// Helper function that deletes the recovery file, if there is one
+fn discard_recovery_file(save_settings: &SaveSettings) {
+    if Path::new(&save_settings.recovery_path).exists() {
+        if let Err(e) = remove_file(&save_settings.recovery_path) {
+            eprintln!("Failed to remove recovery file: {}", e);
+        }
+    }
+}
+
+/// This System spawns the unsaved changes indicator and the prompt text
+fn spawn_save_ui(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section(
+            "Unsaved changes",
+            TextStyle {
+                font_size: 20.,
+                color: Color::srgb(1., 0.85, 0.3),
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.),
+            right: Val::Px(10.),
+            ..default()
+        }),
+        Visibility::Hidden,
+        UnsavedIndicatorMarker,
+        Name::new("Unsaved Indicator"),
+    ));
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+                z_index: ZIndex::Global(100),
+                ..default()
+            },
+            PromptMarker,
+            Name::new("Prompt"),
+        ))
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 24.,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                )
+                .with_style(Style {
+                    padding: UiRect::all(Val::Px(16.)),
+                    ..default()
+                })
+                .with_background_color(Color::srgba(0., 0., 0., 0.8)),
+            );
+        });
+}
+
+/// This System creates the [AutosaveTimer] from the [SaveSettings]
+fn setup_autosave(mut commands: Commands, save_settings: Res<SaveSettings>) {
+    commands.insert_resource(AutosaveTimer(Timer::from_seconds(
+        save_settings.autosave_interval.max(0.),
+        TimerMode::Repeating,
+    )));
+}
+
+/// This System offers to restore the level from the recovery file, if one was left behind
+fn check_recovery_file(save_settings: Res<SaveSettings>, mut save_state: ResMut<SaveState>) {
+    if Path::new(&save_settings.recovery_path).exists() {
+        save_state.prompt = Some(Prompt::Restore);
+    }
+}
+
+/// This System saves the level when the save keybind is pressed
+fn save_on_command(
+    input: Res<PlayerInput>,
+    mut save_state: ResMut<SaveState>,
+    save_settings: Res<SaveSettings>,
+    level: Res<Level>,
+    hovering_block: Res<HoveringBlock>,
+    ls: Res<LevelSettings>,
+) {
+    if input.save_pressed() {
+        save(
+            &mut save_state,
+            &save_settings,
+            &level,
+            &hovering_block,
+            &ls,
+        );
+    }
+}
+
+/// This System periodically writes unsaved changes to the recovery file
+fn autosave(
+    time: Res<Time>,
+    mut timer: ResMut<AutosaveTimer>,
+    mut save_state: ResMut<SaveState>,
+    save_settings: Res<SaveSettings>,
+    level: Res<Level>,
+    hovering_block: Res<HoveringBlock>,
+    ls: Res<LevelSettings>,
+) {
+    if save_settings.autosave_interval <= 0. {
+        return;
+    }
+    timer.tick(time.delta());
+    if !timer.just_finished() || !save_state.recovery_outdated || save_state.recovery_kept {
+        return;
+    }
+    let path = &save_settings.recovery_path;
+    if let Err(e) = write_level_file(path, &level, &hovering_block, &ls) {
+        eprintln!("Failed to autosave level: {}", e);
+    } else {
+        save_state.recovery_outdated = false;
+    }
+}
+
+/// This System asks for confirmation before closing the window with unsaved changes
+fn request_quit(
+    mut close_requests: EventReader<WindowCloseRequested>,
+    mut save_state: ResMut<SaveState>,
+    mut exit: EventWriter<AppExit>,
+) {
+    if close_requests.read().last().is_none() {
+        return;
+    }
+    if save_state.is_dirty() {
+        save_state.prompt = Some(Prompt::Quit);
+    } else {
+        exit.send(AppExit::Success);
+    }
+}
+
+/// This System handles the users answer to the current [Prompt]
+fn answer_prompt(
+    input: Res<PlayerInput>,
+    mut save_state: ResMut<SaveState>,
+    save_settings: Res<SaveSettings>,
+    mut level: ResMut<Level>,
+    mut hovering_block: ResMut<HoveringBlock>,
+    mut ls: ResMut<LevelSettings>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let Some(prompt) = save_state.prompt else {
+        return;
+    };
+    match prompt {
+        Prompt::Quit => {
+            if input.confirm_pressed() {
+                if save(
+                    &mut save_state,
+                    &save_settings,
+                    &level,
+                    &hovering_block,
+                    &ls,
+                ) {
+                    exit.send(AppExit::Success);
+                }
+            } else if input.deny_pressed() {
+                discard_recovery_file(&save_settings);
+                exit.send(AppExit::Success);
+            } else if input.cancel_pressed() {
+                save_state.prompt = None;
+            }
+        }
+        Prompt::Restore => {
+            if input.confirm_pressed() {
+                if let Some(container) = read_level_file(&save_settings.recovery_path) {
+                    apply_level_file(container, &mut level, &mut ls);
+                    // Loading replaced every block, including the ones under the hover preview
+                    hovering_block.preview.clear();
+                    hovering_block.original_material = level
+                        .layer(hovering_block.layer)
+                        .material_map
+                        .get(&hovering_block.hovering)
+                        .copied();
+                    save_state.dirty = true;
+                } else {
+                    eprintln!("Failed to read recovery file");
+                }
+                save_state.prompt = None;
+            } else if input.deny_pressed() {
+                discard_recovery_file(&save_settings);
+                save_state.prompt = None;
+            } else if input.cancel_pressed() {
+                // The recovery file stays on disk, so it is offered again on the next start
+                save_state.recovery_kept = true;
+                save_state.prompt = None;
+            }
+        }
+    }
+}
+
+/// This System shows the unsaved changes indicator and the current [Prompt]
+fn update_save_ui(
+    save_state: Res<SaveState>,
+    mut indicator: Query<&mut Visibility, (With<UnsavedIndicatorMarker>, Without<PromptMarker>)>,
+    mut prompt_node: Query<(&mut Visibility, &Children), With<PromptMarker>>,
+    mut texts: Query<&mut Text>,
+) {
+    if !save_state.is_changed() {
+        return;
+    }
+    indicator.iter_mut().for_each(|mut visibility| {
+        *visibility = if save_state.is_dirty() {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    });
+    let message = match save_state.prompt {
+        Some(Prompt::Quit) => {
+            "You have unsaved changes.\n[Y] Save and quit   [N] Quit without saving   [Esc] Cancel"
+        }
+        Some(Prompt::Restore) => {
+            "Unsaved changes from the last session were found.\n[Y] Restore them   [N] Discard them   [Esc] Decide later"
+        }
+        None => "",
+    };
+    prompt_node
+        .iter_mut()
+        .for_each(|(mut visibility, children)| {
+            *visibility = if save_state.prompt.is_some() {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            };
+            children.iter().for_each(|&child| {
+                if let Ok(mut text) = texts.get_mut(child) {
+                    text.sections[0].value = message.to_string();
+                }
+            });
+        });
+}
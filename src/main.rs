@@ -1,11 +1,14 @@
 #![windows_subsystem = "windows"]
 
 mod camera;
+mod characters;
 mod config;
 mod game_logic;
 mod input;
 mod level_management;
+mod minimap;
 mod player;
+mod settings;
 mod states;
 mod utilities;
 
@@ -13,11 +16,14 @@ use bevy::prelude::*;
 
 use bevy_rapier2d::prelude::*;
 use camera::CameraPlugin;
+use characters::CharacterPlugin;
 use config::ConfigPlugin;
 use game_logic::GameLogicPlugin;
 use input::InputPlugin;
 use level_management::LevelManagementPlugin;
+use minimap::MinimapPlugin;
 use player::PlayerPlugin;
+use settings::SettingsPlugin;
 use states::StatePlugin;
 use utilities::UtilitiesPlugin;
 
@@ -25,13 +31,22 @@ use utilities::UtilitiesPlugin;
 fn main() {
     App::new()
         .add_plugins((
-            DefaultPlugins.set(ImagePlugin::default_nearest()),
+            DefaultPlugins
+                .set(ImagePlugin::default_nearest())
+                .set(WindowPlugin {
+                    // Closing is handled by the SavingPlugin, which asks about unsaved changes first
+                    close_when_requested: false,
+                    ..default()
+                }),
             ConfigPlugin,
             CameraPlugin,
             UtilitiesPlugin,
             InputPlugin,
+            SettingsPlugin,
             LevelManagementPlugin,
+            MinimapPlugin,
             PlayerPlugin,
+            CharacterPlugin,
             GameLogicPlugin,
             StatePlugin,
             RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.),
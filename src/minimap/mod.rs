@@ -0,0 +1,338 @@
+use bevy::{
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+    ui::RelativeCursorPosition,
+};
+use strum::IntoEnumIterator;
+
+use crate::{
+    camera::{
+        movement::follow::{FollowMarker, TargetMarker},
+        split::SplitCameraMarker,
+        zoom::ZoomAnchor,
+        CameraMarker,
+    },
+    config::LevelSettings,
+    level_management::{Layer, Level},
+    player::PlayerMarker,
+    states::AppState,
+};
+
+/// The Plugin containing the minimap shown in the corner of the screen, and in the corner of every split screen viewport
+pub struct MinimapPlugin;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_minimap).add_systems(
+            Update,
+            (
+                update_split_minimaps,
+                update_minimap_image,
+                update_minimap_markers,
+                jump_to_clicked_spot.run_if(in_state(AppState::Editing)),
+            )
+                .chain(),
+        );
+    }
+}
+
+/// The largest size the minimap is shown at, in logical pixels
+const MAX_DISPLAY_SIZE: Vec2 = Vec2::new(200., 120.);
+
+/// The largest number of logical pixels a block is shown with
+const MAX_PIXELS_PER_BLOCK: f32 = 4.;
+
+/// How many empty blocks are shown around the level
+const MARGIN: i32 = 2;
+
+/// How many seconds pass between redraws of the minimap
+const REFRESH_INTERVAL: f32 = 0.2;
+
+/// The Resource holding the minimap image and which part of the level it shows
+#[derive(Resource)]
+struct Minimap {
+    image: Handle<Image>,
+    /// The grid cell shown in the bottom left corner
+    origin: IVec2,
+    /// The number of cells shown horizontally and vertically
+    size: UVec2,
+    /// The number of logical pixels per cell
+    scale: f32,
+    refresh: Timer,
+}
+
+impl Minimap {
+    /// Converts a world position to a position on the minimap, in logical pixels from its top left corner
+    fn to_minimap(&self, world_position: Vec2) -> Vec2 {
+        let cell = world_position / 16.;
+        Vec2::new(
+            cell.x - self.origin.x as f32,
+            (self.origin.y + self.size.y as i32) as f32 - cell.y,
+        ) * self.scale
+    }
+
+    /// Converts a position relative to the minimap, from (0, 0) at the top left to (1, 1) at the bottom right,
+    /// to a world position
+    fn to_world(&self, normalized: Vec2) -> Vec2 {
+        let size = self.size.as_vec2();
+        Vec2::new(
+            self.origin.x as f32 + normalized.x * size.x,
+            self.origin.y as f32 + (1. - normalized.y) * size.y,
+        ) * 16.
+    }
+}
+
+/// Marks the node showing the minimap image
+#[derive(Component)]
+struct MinimapImageMarker;
+
+/// The things marked on top of the minimap
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+enum MinimapMarker {
+    Spawn,
+    Player,
+    Viewport,
+}
+
+/// Helper function that creates an image of the given size, filled with transparent pixels
+fn empty_image(size: UVec2) -> Image {
+    Image::new_fill(
+        Extent3d {
+            width: size.x,
+            height: size.y,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    )
+}
+
+/// Helper function that spawns a minimap node in the top right corner of the viewport of the given camera,
+/// or of the main camera if there is none
+fn spawn_minimap_node(commands: &mut Commands, minimap: &Minimap, camera: Option<Entity>) {
+    let mut node = commands.spawn((
+        ImageBundle {
+            image: UiImage::new(minimap.image.clone()),
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(40.),
+                right: Val::Px(10.),
+                width: Val::Px(minimap.size.x as f32 * minimap.scale),
+                height: Val::Px(minimap.size.y as f32 * minimap.scale),
+                ..default()
+            },
+            background_color: Color::srgba(0., 0., 0., 0.5).into(),
+            ..default()
+        },
+        Interaction::default(),
+        RelativeCursorPosition::default(),
+        MinimapImageMarker,
+        Name::new("Minimap"),
+    ));
+    if let Some(camera) = camera {
+        node.insert(TargetCamera(camera));
+    }
+    node.with_children(|parent| {
+        [
+            (MinimapMarker::Viewport, Color::NONE, Color::WHITE),
+            (MinimapMarker::Spawn, Color::srgb(0.2, 1., 0.3), Color::NONE),
+            (
+                MinimapMarker::Player,
+                Color::srgb(1., 0.2, 0.2),
+                Color::NONE,
+            ),
+        ]
+        .into_iter()
+        .for_each(|(marker, background, border)| {
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        border: UiRect::all(Val::Px(1.)),
+                        ..default()
+                    },
+                    background_color: background.into(),
+                    border_color: border.into(),
+                    ..default()
+                },
+                marker,
+            ));
+        });
+    });
+}
+
+/// This System spawns the minimap in the top right corner
+fn spawn_minimap(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    let minimap = Minimap {
+        image: images.add(empty_image(UVec2::ONE)),
+        origin: IVec2::ZERO,
+        size: UVec2::ONE,
+        scale: 1.,
+        refresh: Timer::from_seconds(REFRESH_INTERVAL, TimerMode::Repeating),
+    };
+    spawn_minimap_node(&mut commands, &minimap, None);
+    commands.insert_resource(minimap);
+}
+
+/// This System gives every split screen camera its own minimap, and removes it again along with the camera
+fn update_split_minimaps(
+    mut commands: Commands,
+    minimap: Res<Minimap>,
+    added: Query<Entity, Added<SplitCameraMarker>>,
+    cameras: Query<(), With<Camera>>,
+    nodes: Query<(Entity, &TargetCamera), With<MinimapImageMarker>>,
+) {
+    added.iter().for_each(|camera| {
+        spawn_minimap_node(&mut commands, &minimap, Some(camera));
+    });
+    nodes
+        .iter()
+        .filter(|(_, target)| cameras.get(target.entity()).is_err())
+        .for_each(|(entity, _)| {
+            commands.entity(entity).despawn_recursive();
+        });
+}
+
+/// This System periodically redraws the minimap, with one pixel per block
+fn update_minimap_image(
+    time: Res<Time>,
+    level: Res<Level>,
+    ls: Res<LevelSettings>,
+    mut minimap: ResMut<Minimap>,
+    mut images: ResMut<Assets<Image>>,
+    mut node: Query<&mut Style, With<MinimapImageMarker>>,
+) {
+    minimap.refresh.tick(time.delta());
+    if !minimap.refresh.just_finished() {
+        return;
+    }
+    let spawn = (ls.spawn_location.translation.truncate() / 16.)
+        .floor()
+        .as_ivec2();
+    let (min, max) = level.bounds().map_or((spawn, spawn), |bounds| {
+        let min = (bounds.min / 16.).round().as_ivec2();
+        let max = (bounds.max / 16.).round().as_ivec2() - IVec2::ONE;
+        (min.min(spawn), max.max(spawn))
+    });
+    let origin = min - IVec2::splat(MARGIN);
+    let size = (max - origin + IVec2::splat(MARGIN + 1)).as_uvec2();
+    let mut image = empty_image(size);
+    Layer::iter().for_each(|layer| {
+        level
+            .layer(layer)
+            .material_map
+            .iter()
+            .for_each(|(&(x, y), material)| {
+                let (column, row) = ((x - origin.x) as u32, size.y - 1 - (y - origin.y) as u32);
+                let index = ((row * size.x + column) * 4) as usize;
+                let mut color = material.color().to_srgba();
+                if layer == Layer::Background {
+                    color = color.darker(0.25);
+                }
+                image.data[index..index + 4].copy_from_slice(&color.to_u8_array());
+            });
+    });
+    if let Some(target) = images.get_mut(&minimap.image) {
+        *target = image;
+    }
+    let scale = (MAX_DISPLAY_SIZE / size.as_vec2())
+        .min_element()
+        .min(MAX_PIXELS_PER_BLOCK);
+    minimap.origin = origin;
+    minimap.size = size;
+    minimap.scale = scale;
+    node.iter_mut().for_each(|mut style| {
+        style.width = Val::Px(size.x as f32 * scale);
+        style.height = Val::Px(size.y as f32 * scale);
+    });
+}
+
+/// This System moves the spawn, player and viewport markers of every minimap to where they are in the level
+///
+/// Every minimap shows the view of the camera it is drawn by, and the first player that camera follows
+fn update_minimap_markers(
+    minimap: Res<Minimap>,
+    ls: Res<LevelSettings>,
+    players: Query<(&TargetMarker, &Transform), With<PlayerMarker>>,
+    main_camera: Query<Entity, With<CameraMarker>>,
+    cameras: Query<(
+        &GlobalTransform,
+        &OrthographicProjection,
+        Option<&FollowMarker>,
+    )>,
+    nodes: Query<(&Children, Option<&TargetCamera>), With<MinimapImageMarker>>,
+    mut markers: Query<(&MinimapMarker, &mut Style, &mut Visibility)>,
+) {
+    nodes.iter().for_each(|(children, target)| {
+        let Some(Ok((camera_transform, projection, follow_marker))) = target
+            .map(TargetCamera::entity)
+            .or(main_camera.get_single().ok())
+            .map(|camera| cameras.get(camera))
+        else {
+            return;
+        };
+        let camera_position = camera_transform.translation().truncate();
+        let player = players
+            .iter()
+            .find(|(marker, _)| follow_marker.is_some_and(|follow| follow.follows(marker)))
+            .or(players.iter().next())
+            .map(|(_, transform)| transform);
+        children.iter().for_each(|&child| {
+            let Ok((marker, mut style, mut visibility)) = markers.get_mut(child) else {
+                return;
+            };
+            let area = match marker {
+                MinimapMarker::Spawn => Some(Rect::from_center_size(
+                    ls.spawn_location.translation.truncate(),
+                    Vec2::splat(16.),
+                )),
+                MinimapMarker::Player => player.map(|transform| {
+                    Rect::from_center_size(transform.translation.truncate(), Vec2::splat(16.))
+                }),
+                MinimapMarker::Viewport => Some(Rect::from_corners(
+                    camera_position + projection.area.min,
+                    camera_position + projection.area.max,
+                )),
+            };
+            let Some(area) = area else {
+                *visibility = Visibility::Hidden;
+                return;
+            };
+            *visibility = Visibility::Inherited;
+            // The top left corner of the area, as y points down on the minimap
+            let corner = minimap.to_minimap(Vec2::new(area.min.x, area.max.y));
+            let size = (area.size() / 16. * minimap.scale).max(Vec2::splat(3.));
+            style.left = Val::Px(corner.x);
+            style.top = Val::Px(corner.y);
+            style.width = Val::Px(size.x);
+            style.height = Val::Px(size.y);
+        });
+    });
+}
+
+/// This System moves the camera to the spot of the minimap that is clicked or dragged over
+fn jump_to_clicked_spot(
+    mut commands: Commands,
+    minimap: Res<Minimap>,
+    node: Query<(&Interaction, &RelativeCursorPosition), With<MinimapImageMarker>>,
+    mut camera: Query<(Entity, &mut Transform), With<CameraMarker>>,
+) {
+    let Ok((interaction, cursor)) = node.get_single() else {
+        return;
+    };
+    if *interaction != Interaction::Pressed {
+        return;
+    }
+    let Some(normalized) = cursor.normalized else {
+        return;
+    };
+    let position = minimap.to_world(normalized.clamp(Vec2::ZERO, Vec2::ONE));
+    let (entity, mut transform) = camera.single_mut();
+    transform.translation = position.extend(transform.translation.z);
+    commands.entity(entity).remove::<ZoomAnchor>();
+}
@@ -1,12 +1,14 @@
 use bevy::prelude::*;
+use bevy_rapier2d::prelude::Velocity;
+use bevy_tnua::prelude::*;
 
 use crate::{
-    input::PlayerInput,
+    config::AnimationSettings,
     states::AppState,
-    utilities::assets::{PlayerAnimationIndices, PlayerAnimationMap, PlayerAnimationState},
+    utilities::{animation::SpriteAnimation, assets::PlayerAnimationState},
 };
 
-use super::PlayerMarker;
+use super::{CrouchState, DashState, LandingState};
 
 /// A Plugin for managing the player animation
 pub struct PlayerAnimationPlugin;
@@ -17,41 +19,86 @@ impl Plugin for PlayerAnimationPlugin {
     }
 }
 
-/// A System that changes the players animation, depending on state
+/// A System that changes the animation of every player, depending on how they are actually moving
 fn animate_player(
-    player_input: Res<PlayerInput>,
-    player_animation_map: Res<PlayerAnimationMap>,
-    mut player_animation_indices: ResMut<PlayerAnimationIndices>,
-    mut query: Query<(&mut TextureAtlas, &mut Handle<Image>), With<PlayerMarker>>,
-    mut player_animation_state_res: ResMut<PlayerAnimationState>,
+    settings: Res<AnimationSettings>,
+    time: Res<Time>,
+    mut query: Query<(
+        &TnuaController,
+        &Velocity,
+        &Sprite,
+        &DashState,
+        &CrouchState,
+        &mut LandingState,
+        &mut SpriteAnimation,
+    )>,
 ) {
-    let mut player_animation_state = PlayerAnimationState::IDLE;
-    if player_input.direction_vector().x.abs() >= 0.1 {
-        player_animation_state = PlayerAnimationState::RUN;
-    }
-    if player_animation_state == *player_animation_state_res {
-        return;
+    query.iter_mut().for_each(
+        |(
+            controller,
+            velocity,
+            sprite,
+            dash_state,
+            crouch_state,
+            mut landing_state,
+            mut animation,
+        )| {
+            let airborne = controller.is_airborne().unwrap_or(true);
+            landing_state.landing = (landing_state.landing - time.delta_seconds()).max(0.);
+            if landing_state.airborne
+                && !airborne
+                && landing_state.fall_speed >= settings.landing_speed
+            {
+                landing_state.landing = settings.landing_time;
+            }
+            landing_state.airborne = airborne;
+            landing_state.fall_speed = -velocity.linvel.y;
+            let state = choose_animation_state(
+                &settings,
+                velocity.linvel,
+                airborne,
+                sprite.flip_x,
+                dash_state,
+                crouch_state,
+                &landing_state,
+            );
+            if animation.clip() != state.as_ref() {
+                animation.play(state.as_ref());
+            }
+        },
+    );
+}
+
+/// Helper function that picks the animation for the given movement of the player
+///
+/// Actions like dashing and crouching win over being in the air, which wins over the grounded animations
+fn choose_animation_state(
+    settings: &AnimationSettings,
+    velocity: Vec2,
+    airborne: bool,
+    facing_left: bool,
+    dash_state: &DashState,
+    crouch_state: &CrouchState,
+    landing_state: &LandingState,
+) -> PlayerAnimationState {
+    let facing = if facing_left { -1. } else { 1. };
+    if dash_state.dashing {
+        PlayerAnimationState::DASH
+    } else if crouch_state.sliding() {
+        PlayerAnimationState::CROUCH_SLIDE
+    } else if crouch_state.crouching {
+        PlayerAnimationState::CROUCH
+    } else if airborne && velocity.y > settings.rise_speed {
+        PlayerAnimationState::JUMP
+    } else if airborne {
+        PlayerAnimationState::FALL
+    } else if landing_state.landing > 0. {
+        PlayerAnimationState::LAND
+    } else if velocity.x * facing < -settings.turn_speed {
+        PlayerAnimationState::TURN
+    } else if velocity.x.abs() > settings.run_speed {
+        PlayerAnimationState::RUN
+    } else {
+        PlayerAnimationState::IDLE
     }
-    *player_animation_state_res = player_animation_state;
-    let indices = match player_animation_state {
-        PlayerAnimationState::FALL => 1,
-        PlayerAnimationState::IDLE => 11,
-        PlayerAnimationState::JUMP => 1,
-        PlayerAnimationState::RUN => 12,
-    };
-    player_animation_indices.last = indices - 1;
-    let (mut atlas, mut image) = query.single_mut();
-    atlas.index = 0;
-    atlas.layout = player_animation_map
-        .0
-        .get(&player_animation_state)
-        .unwrap()
-        .texture_atlas_layout
-        .clone();
-    *image = player_animation_map
-        .0
-        .get(&player_animation_state)
-        .unwrap()
-        .texture
-        .clone();
 }
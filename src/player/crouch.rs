@@ -0,0 +1,123 @@
+use bevy::{prelude::*, sprite::Anchor};
+use bevy_rapier2d::prelude::*;
+use bevy_tnua::prelude::*;
+use bevy_tnua_rapier2d::TnuaRapier2dSensorShape;
+
+use super::{
+    movement::apply_movement, CrouchState, DashState, PlayerColliders, PlayerNumber, Speed,
+};
+use crate::{
+    config::{CharacterControllerSettings, PlayerSettings},
+    input::PlayerInputs,
+    states::AppState,
+    utilities::animation::{AnimationClips, SpriteAnimation},
+};
+
+/// The Plugin that lets the player crouch, crawl and slide
+pub struct PlayerCrouchPlugin;
+
+impl Plugin for PlayerCrouchPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            update_crouch
+                .before(apply_movement)
+                .run_if(in_state(AppState::Playing)),
+        );
+    }
+}
+
+/// This System makes the player crouch while crouch is held, and stand back up once it is released and there is room above them
+///
+/// Crouching shrinks the collider and the sensor shape, and squashes the sprite to match.
+/// Crouching on the ground while running faster than crawl speed starts a crouch slide
+#[allow(clippy::type_complexity)]
+fn update_crouch(
+    ccs: Res<CharacterControllerSettings>,
+    ps: Res<PlayerSettings>,
+    animation_clips: Res<AnimationClips>,
+    inputs: Res<PlayerInputs>,
+    time: Res<Time>,
+    rapier_context: Res<RapierContext>,
+    mut query: Query<(
+        &PlayerNumber,
+        &TnuaController,
+        &Transform,
+        &Velocity,
+        &Speed,
+        &PlayerColliders,
+        &DashState,
+        &mut CrouchState,
+        &mut Collider,
+        &mut TnuaRapier2dSensorShape,
+        &mut Sprite,
+        Option<&SpriteAnimation>,
+    )>,
+) {
+    query.iter_mut().for_each(
+        |(
+            number,
+            controller,
+            transform,
+            velocity,
+            speed,
+            colliders,
+            dash_state,
+            mut crouch_state,
+            mut collider,
+            mut sensor_shape,
+            mut sprite,
+            animation,
+        )| {
+            crouch_state.slide = (crouch_state.slide - time.delta_seconds()).max(0.);
+            let wants_to_crouch = inputs.get(number.0).crouch_held() && !dash_state.dashing;
+            if wants_to_crouch == crouch_state.crouching {
+                return;
+            }
+            if !wants_to_crouch {
+                let (offset, room) = colliders.standing_room();
+                let blocked = rapier_context
+                    .intersection_with_shape(
+                        transform.translation.truncate() + offset,
+                        0.,
+                        &room,
+                        QueryFilter::only_fixed().exclude_sensors(),
+                    )
+                    .is_some();
+                if blocked {
+                    return;
+                }
+            }
+            let grounded = !controller.is_airborne().unwrap_or(true);
+            let running = velocity.linvel.x.abs() > ccs.crawl_speed_factor * speed.0;
+            if wants_to_crouch && grounded && running {
+                crouch_state.slide = ccs.crouch_slide_time;
+                crouch_state.slide_direction = velocity.linvel.x.signum();
+            }
+            crouch_state.crouching = wants_to_crouch;
+            *collider = colliders.collider(wants_to_crouch);
+            sensor_shape.0 = colliders.sensor_shape(wants_to_crouch);
+            if !wants_to_crouch {
+                (sprite.custom_size, sprite.anchor) = (None, Anchor::Center);
+                return;
+            }
+            let frame_size = animation.and_then(|animation| {
+                animation_clips
+                    .get(&animation.set, animation.clip())
+                    .map(|clip| clip.frame_size)
+            });
+            if let Some(frame_size) = frame_size {
+                (sprite.custom_size, sprite.anchor) =
+                    squashed_sprite(frame_size, ps.crouch_sprite_height);
+            }
+        },
+    );
+}
+
+/// Helper function that returns the size and anchor of a sprite with the given frame size squashed to the given fraction of its height, keeping its bottom in place
+fn squashed_sprite(frame_size: Vec2, height: f32) -> (Option<Vec2>, Anchor) {
+    (
+        Some(Vec2::new(frame_size.x, frame_size.y * height)),
+        Anchor::Custom(Vec2::new(0., 0.5 / height - 0.5)),
+    )
+}
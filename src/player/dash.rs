@@ -0,0 +1,152 @@
+use bevy::prelude::*;
+use bevy_tnua::{builtins::TnuaBuiltinDash, prelude::*, TnuaAction};
+
+use super::{movement::apply_movement, DashState, PlayerNumber};
+use crate::{
+    config::{CharacterControllerSettings, LevelSettings},
+    input::PlayerInputs,
+    states::AppState,
+};
+
+/// The Plugin that lets the player dash
+pub struct PlayerDashPlugin;
+
+impl Plugin for PlayerDashPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                apply_dash
+                    .after(apply_movement)
+                    .in_set(TnuaUserControlsSystemSet),
+                spawn_dash_trail.after(apply_dash),
+            )
+                .run_if(in_state(AppState::Playing)),
+        )
+        .add_systems(Update, fade_dash_trail);
+    }
+}
+
+/// How many seconds pass between two afterimages of the dash trail
+const TRAIL_INTERVAL: f32 = 0.03;
+
+/// How many seconds an afterimage of the dash trail takes to fade out
+const TRAIL_LIFETIME: f32 = 0.25;
+
+/// How opaque an afterimage of the dash trail starts out
+const TRAIL_ALPHA: f32 = 0.5;
+
+/// Marks a fading afterimage of the player, left behind while dashing
+#[derive(Component)]
+struct DashTrail(Timer);
+
+/// This System starts a dash when dash is pressed, and keeps feeding it to [TnuaController] until it is over
+///
+/// It runs after [apply_movement], so a dash takes the place of a jump that is fed in the same frame
+fn apply_dash(
+    ccs: Res<CharacterControllerSettings>,
+    ls: Res<LevelSettings>,
+    inputs: Res<PlayerInputs>,
+    time: Res<Time>,
+    mut query: Query<(&PlayerNumber, &mut TnuaController, &mut DashState, &Sprite)>,
+) {
+    query
+        .iter_mut()
+        .for_each(|(number, mut controller, mut dash_state, sprite)| {
+            let input = inputs.get(number.0);
+            dash_state.cooldown = (dash_state.cooldown - time.delta_seconds()).max(0.);
+            // Tnua runs after this System in the same frame, so a dash that was just started is already reported on the next one
+            if dash_state.dashing && controller.action_name() != Some(TnuaBuiltinDash::NAME) {
+                dash_state.dashing = false;
+            }
+            if !dash_state.dashing && !controller.is_airborne().unwrap_or(true) {
+                dash_state.refreshed = true;
+            }
+            if input.dash_pressed()
+                && !dash_state.dashing
+                && dash_state.refreshed
+                && dash_state.cooldown <= 0.
+            {
+                let x = input.direction_vector().x;
+                let direction = if x != 0. {
+                    x.signum()
+                } else if sprite.flip_x {
+                    -1.
+                } else {
+                    1.
+                };
+                dash_state.displacement = Vec2::new(direction * ccs.dash_distance, 0.);
+                dash_state.dashing = true;
+                dash_state.refreshed = false;
+                dash_state.cooldown = ls.dash_cooldown;
+            }
+            if dash_state.dashing {
+                controller.action(TnuaBuiltinDash {
+                    displacement: dash_state.displacement.extend(0.),
+                    desired_forward: dash_state.displacement.normalize_or_zero().extend(0.),
+                    ..ccs.builtin_dash
+                });
+            }
+        });
+}
+
+/// This System leaves afterimages of the player behind while they dash
+fn spawn_dash_trail(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut since_last: Local<f32>,
+    query: Query<(
+        &DashState,
+        &Transform,
+        &Handle<Image>,
+        &TextureAtlas,
+        &Sprite,
+    )>,
+) {
+    *since_last += time.delta_seconds();
+    if *since_last < TRAIL_INTERVAL {
+        return;
+    }
+    *since_last = 0.;
+    query
+        .iter()
+        .filter(|(dash_state, ..)| dash_state.dashing)
+        .for_each(|(_, transform, texture, atlas, sprite)| {
+            commands.spawn((
+                SpriteBundle {
+                    texture: texture.clone(),
+                    // Slightly behind the player, so the afterimages never cover them
+                    transform: transform.with_translation(transform.translation - Vec3::Z * 0.1),
+                    sprite: Sprite {
+                        color: sprite.color.with_alpha(TRAIL_ALPHA),
+                        flip_x: sprite.flip_x,
+                        ..default()
+                    },
+                    ..default()
+                },
+                atlas.clone(),
+                DashTrail(Timer::from_seconds(TRAIL_LIFETIME, TimerMode::Once)),
+                Name::new("Dash Trail"),
+            ));
+        });
+}
+
+/// This System fades out the afterimages of the dash trail and removes them once they are invisible
+fn fade_dash_trail(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut DashTrail, &mut Sprite)>,
+) {
+    query
+        .iter_mut()
+        .for_each(|(entity, mut trail, mut sprite)| {
+            trail.0.tick(time.delta());
+            if trail.0.finished() {
+                commands.entity(entity).despawn();
+                return;
+            }
+            sprite
+                .color
+                .set_alpha(TRAIL_ALPHA * trail.0.fraction_remaining());
+        });
+}
@@ -1,10 +1,21 @@
 mod animation;
+mod crouch;
+mod dash;
 mod movement;
+mod wall;
 
-use crate::camera::movement::follow::{Target, TargetMarker};
+use crate::{
+    camera::movement::follow::{Target, TargetMarker},
+    config::PlayerSettings,
+};
 use animation::PlayerAnimationPlugin;
 use bevy::prelude::*;
+use bevy_rapier2d::prelude::Collider;
+use crouch::PlayerCrouchPlugin;
+use dash::PlayerDashPlugin;
 use movement::PlayerMovementPlugin;
+use serde::Deserialize;
+use wall::PlayerWallPlugin;
 
 /// A Plugin containing everything related to the player
 pub struct PlayerPlugin;
@@ -12,16 +23,29 @@ pub struct PlayerPlugin;
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(PlayerMovementPlugin)
-            .add_plugins(PlayerAnimationPlugin);
+            .add_plugins(PlayerAnimationPlugin)
+            .add_plugins(PlayerWallPlugin)
+            .add_plugins(PlayerDashPlugin)
+            .add_plugins(PlayerCrouchPlugin);
     }
 }
 
+/// How many players can play at once, sharing the keyboard or each using their own gamepad
+pub const MAX_PLAYERS: usize = 2;
+
+/// How strongly the tint of a player is mixed into the color of their character
+const PLAYER_TINT_STRENGTH: f32 = 0.4;
+
 /// A Bundle containing everything needed by the player entity
 #[derive(Bundle)]
 pub struct PlayerBundle {
     pub marker: PlayerMarker,
-    pub jump_height: JumpHeight,
-    pub speed: Speed,
+    pub number: PlayerNumber,
+    pub jump_state: JumpState,
+    pub wall_state: WallState,
+    pub dash_state: DashState,
+    pub crouch_state: CrouchState,
+    pub landing_state: LandingState,
     pub target_marker: TargetMarker,
     pub name: Name,
 }
@@ -30,9 +54,13 @@ impl Default for PlayerBundle {
     fn default() -> PlayerBundle {
         PlayerBundle {
             marker: PlayerMarker,
-            jump_height: JumpHeight(200.),
-            speed: Speed(1000.),
-            target_marker: TargetMarker::new(Target::Player),
+            number: PlayerNumber(0),
+            jump_state: JumpState::default(),
+            wall_state: WallState::default(),
+            dash_state: DashState::default(),
+            crouch_state: CrouchState::default(),
+            landing_state: LandingState::default(),
+            target_marker: TargetMarker::new(Target::Player(0)),
             name: Name::new("Player"),
         }
     }
@@ -42,10 +70,184 @@ impl Default for PlayerBundle {
 #[derive(Component, Clone, Copy)]
 pub struct PlayerMarker;
 
-/// The Component that defines the players jump height
-#[derive(Component, Clone, Copy)]
-pub struct JumpHeight(pub f32);
+/// The Component that tells the players apart, starting at 0 for the first player. It decides which input a player moves with
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+pub struct PlayerNumber(pub usize);
+
+impl PlayerNumber {
+    /// Returns where the player spawns, offset from the given spawn point for every player before them
+    pub fn spawn_location(&self, spawn: Transform, ps: &PlayerSettings) -> Transform {
+        spawn.with_translation(spawn.translation + ps.spawn_offset * self.0 as f32)
+    }
+
+    /// Returns the color the sprite of the player is drawn with, which is the color of their character with their tint mixed in
+    pub fn color(&self, character_color: Color, ps: &PlayerSettings) -> Color {
+        match ps.player_tints.get(self.0).copied().flatten() {
+            Some(tint) => character_color.mix(&tint, PLAYER_TINT_STRENGTH),
+            None => character_color,
+        }
+    }
+}
 
 /// The Component that defines the players speed
 #[derive(Component, Clone, Copy)]
 pub struct Speed(pub f32);
+
+/// The Component that defines the height of the players jump when jump is held until its peak
+#[derive(Component, Clone, Copy)]
+pub struct JumpHeight(pub f32);
+
+/// The Component that defines the sizes of the players colliders
+#[derive(Component, Clone, Copy, Deserialize)]
+pub struct PlayerColliders {
+    pub collider_size: Vec2,
+    pub sensor_collider_size: Vec2,
+    /// The size of the collider while crouching. Its bottom stays where it is when standing
+    pub crouch_collider_size: Vec2,
+    pub crouch_sensor_collider_size: Vec2,
+}
+
+impl PlayerColliders {
+    /// Returns the collider of the player, shrunk down if crouching
+    pub fn collider(&self, crouching: bool) -> Collider {
+        let bottom = standing_bottom(self.collider_size);
+        if crouching {
+            capsule(self.crouch_collider_size, bottom)
+        } else {
+            capsule(self.collider_size, bottom)
+        }
+    }
+
+    /// Returns the shape Tnua uses to find the ground below the player, shrunk down if crouching
+    pub fn sensor_shape(&self, crouching: bool) -> Collider {
+        let bottom = standing_bottom(self.sensor_collider_size);
+        if crouching {
+            capsule(self.crouch_sensor_collider_size, bottom)
+        } else {
+            capsule(self.sensor_collider_size, bottom)
+        }
+    }
+
+    /// Returns the offset and shape of the space above a crouching player, that has to be free for them to stand up
+    pub fn standing_room(&self) -> (Vec2, Collider) {
+        let bottom = standing_bottom(self.collider_size);
+        let crouched_top = bottom + self.crouch_collider_size.y + self.crouch_collider_size.x;
+        let standing_top = bottom + self.collider_size.y + self.collider_size.x;
+        (
+            Vec2::new(0., (crouched_top + standing_top) / 2.),
+            Collider::cuboid(
+                self.collider_size.x / 2.,
+                (standing_top - crouched_top) / 2.,
+            ),
+        )
+    }
+}
+
+/// Helper function that returns how far below its center the bottom of a standing capsule of the given size is
+fn standing_bottom(size: Vec2) -> f32 {
+    -size.y / 2. - 2. - size.x / 2.
+}
+
+/// Helper function that builds an upright capsule of the given size, whose bottom is at the given height
+fn capsule(size: Vec2, bottom: f32) -> Collider {
+    let radius = size.x / 2.;
+    Collider::capsule(
+        Vec2::new(0., bottom + radius),
+        Vec2::new(0., bottom + radius + size.y),
+        radius,
+    )
+}
+
+/// The Component that keeps track of when the player last pressed jump and last stood on the ground
+#[derive(Component, Clone, Copy)]
+pub struct JumpState {
+    /// Seconds since jump was last pressed
+    pub since_pressed: f32,
+    /// Seconds since the player last stood on the ground
+    pub since_grounded: f32,
+    /// Whether the jump that was started is still going on
+    pub jumping: bool,
+    /// The height the current jump started at
+    pub takeoff_height: f32,
+}
+
+impl Default for JumpState {
+    fn default() -> Self {
+        JumpState {
+            since_pressed: f32::INFINITY,
+            since_grounded: f32::INFINITY,
+            jumping: false,
+            takeoff_height: 0.,
+        }
+    }
+}
+
+/// The Component that keeps track of walls beside the player and of the last wall jump
+#[derive(Component, Clone, Copy, Default)]
+pub struct WallState {
+    /// The side of the player a wall was found on. Negative is left, positive is right
+    pub side: Option<f32>,
+    /// Seconds left until horizontal input is handled again after a wall jump
+    pub lockout: f32,
+    /// The horizontal direction of the last wall jump
+    pub jump_direction: f32,
+}
+
+impl WallState {
+    /// Returns whether horizontal input is ignored because of a recent wall jump
+    pub fn locked_out(&self) -> bool {
+        self.lockout > 0.
+    }
+}
+
+/// The Component that keeps track of the dash of the player
+#[derive(Component, Clone, Copy)]
+pub struct DashState {
+    /// Seconds left until the player can dash again
+    pub cooldown: f32,
+    /// Whether the player has touched the ground since the last dash
+    pub refreshed: bool,
+    /// Whether a dash is going on
+    pub dashing: bool,
+    /// How far the current dash moves the player
+    pub displacement: Vec2,
+}
+
+impl Default for DashState {
+    fn default() -> Self {
+        DashState {
+            cooldown: 0.,
+            refreshed: true,
+            dashing: false,
+            displacement: Vec2::ZERO,
+        }
+    }
+}
+
+/// The Component that keeps track of whether the player is crouching or sliding
+#[derive(Component, Clone, Copy, Default)]
+pub struct CrouchState {
+    pub crouching: bool,
+    /// Seconds left of the crouch slide
+    pub slide: f32,
+    /// The horizontal direction of the crouch slide
+    pub slide_direction: f32,
+}
+
+impl CrouchState {
+    /// Returns whether the player is sliding along after crouching while running
+    pub fn sliding(&self) -> bool {
+        self.crouching && self.slide > 0.
+    }
+}
+
+/// The Component that keeps track of how the player last landed, for the landing animation
+#[derive(Component, Clone, Copy, Default)]
+pub struct LandingState {
+    /// Whether the player was in the air last frame
+    pub airborne: bool,
+    /// The downwards speed of the player last frame
+    pub fall_speed: f32,
+    /// Seconds left of the landing animation
+    pub landing: f32,
+}
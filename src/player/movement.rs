@@ -1,7 +1,12 @@
-use super::{JumpHeight, Speed};
-use crate::{config::CharacterControllerSettings, input::PlayerInput, states::AppState};
+use super::{CrouchState, JumpHeight, JumpState, PlayerNumber, Speed, WallState};
+use crate::{
+    config::CharacterControllerSettings,
+    input::{PlayerInput, PlayerInputs},
+    states::AppState,
+};
 use bevy::prelude::*;
-use bevy_tnua::{builtins::TnuaBuiltinCrouch, prelude::*};
+use bevy_rapier2d::prelude::RapierConfiguration;
+use bevy_tnua::{prelude::*, TnuaAction};
 use bevy_tnua_rapier2d::*;
 
 /// The Plugin that implements movement for the player
@@ -9,46 +14,156 @@ pub struct PlayerMovementPlugin;
 
 impl Plugin for PlayerMovementPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, apply_movement.run_if(in_state(AppState::Playing)))
-            .add_plugins((
-                TnuaRapier2dPlugin::default(),
-                TnuaControllerPlugin::default(),
-            ));
+        app.add_systems(
+            Update,
+            apply_movement
+                .in_set(TnuaUserControlsSystemSet)
+                .run_if(in_state(AppState::Playing)),
+        )
+        .add_plugins((
+            TnuaRapier2dPlugin::default(),
+            TnuaControllerPlugin::default(),
+        ));
     }
 }
 
-/// A system that moves the player according to [bevy_tnua] and [bevy_tnua_rapier2d]
-fn apply_movement(
+/// A system that moves every player according to [bevy_tnua] and [bevy_tnua_rapier2d], using their own input
+#[allow(clippy::type_complexity)]
+pub fn apply_movement(
     ccs: Res<CharacterControllerSettings>,
-    mut query: Query<(&mut TnuaController, &Speed, &JumpHeight, &mut Sprite)>,
-    input: Res<PlayerInput>,
+    rapier_config: Res<RapierConfiguration>,
+    mut query: Query<(
+        &PlayerNumber,
+        &mut TnuaController,
+        &Speed,
+        &JumpHeight,
+        &mut JumpState,
+        &WallState,
+        &CrouchState,
+        &mut Sprite,
+        &Transform,
+    )>,
+    inputs: Res<PlayerInputs>,
+    time: Res<Time>,
 ) {
-    query
-        .iter_mut()
-        .for_each(|(mut controller, speed, jump_height, mut sprite)| {
+    query.iter_mut().for_each(
+        |(
+            number,
+            mut controller,
+            speed,
+            jump_height,
+            mut jump_state,
+            wall_state,
+            crouch_state,
+            mut sprite,
+            transform,
+        )| {
+            let input = inputs.get(number.0);
+            // Right after a wall jump the player keeps moving away from the wall, regardless of input
+            let direction = if wall_state.locked_out() {
+                Vec2::new(wall_state.jump_direction, 0.)
+            } else if crouch_state.sliding() {
+                Vec2::new(crouch_state.slide_direction, 0.)
+            } else {
+                input.direction_vector()
+            };
+            let speed = if crouch_state.sliding() {
+                // The slide slows down from running speed to crawl speed
+                let progress = 1. - crouch_state.slide / ccs.crouch_slide_time;
+                speed.0 * (1. - progress * (1. - ccs.crawl_speed_factor))
+            } else if crouch_state.crouching {
+                speed.0 * ccs.crawl_speed_factor
+            } else {
+                speed.0
+            };
+            // A partially tilted stick walks slower, but the character still faces the full direction
             controller.basis(TnuaBuiltinWalk {
-                desired_velocity: input.direction_vector().extend(0.) * speed.0,
-                desired_forward: input.direction_vector().extend(0.),
+                desired_velocity: direction.extend(0.) * speed,
+                desired_forward: direction.normalize_or_zero().extend(0.),
                 ..ccs.builtin_walk
             });
 
-            if input.jump_pressed() {
+            update_jump_state(
+                &mut jump_state,
+                &controller,
+                &ccs,
+                input,
+                crouch_state.crouching,
+                jump_height.0,
+                transform.translation.y,
+                time.delta_seconds(),
+            );
+            if jump_state.jumping {
+                // Tnua adds its extra gravity on top of the gravity of the physics, so a multiplier of 1 adds none
+                let gravity = rapier_config.gravity.length();
                 controller.action(TnuaBuiltinJump {
                     height: jump_height.0,
-                    ..default()
+                    fall_extra_gravity: (ccs.fall_gravity_multiplier - 1.) * gravity,
+                    shorten_extra_gravity: (ccs.release_gravity_multiplier - 1.) * gravity,
+                    ..ccs.builtin_jump
                 });
             }
 
-            if input.crouch_pressed() {
-                controller.action(TnuaBuiltinCrouch::default());
+            if crouch_state.crouching && !jump_state.jumping {
+                controller.action(ccs.builtin_crouch.clone());
             }
 
-            let x = input.direction_vector().x;
+            let x = direction.x;
 
             if x > 0. {
                 sprite.flip_x = false
             } else if x < 0. {
                 sprite.flip_x = true
             }
-        })
+        },
+    )
+}
+
+/// Helper function that decides whether a jump should be started or continued
+///
+/// A jump starts when it was pressed within the buffer window and the player was on the ground within the coyote window.
+/// It is then fed to [TnuaController] every frame, until Tnua reports that the jump action has ended,
+/// or until jump is released after the minimum jump height was reached, which makes Tnua cut the jump short.
+/// Tnua runs after this System in the same frame, so a jump that was just started is already reported on the next one
+#[allow(clippy::too_many_arguments)]
+fn update_jump_state(
+    jump_state: &mut JumpState,
+    controller: &TnuaController,
+    ccs: &CharacterControllerSettings,
+    input: PlayerInput,
+    crouching: bool,
+    max_height: f32,
+    height: f32,
+    delta: f32,
+) {
+    let airborne = controller.is_airborne().unwrap_or(true);
+    let jump_ongoing = controller.action_name() == Some(TnuaBuiltinJump::NAME);
+    let released_early = !input.jump_held()
+        && height - jump_state.takeoff_height >= ccs.min_jump_height.min(max_height);
+    if jump_state.jumping && (!jump_ongoing || released_early) {
+        jump_state.jumping = false;
+    }
+    jump_state.since_grounded = if airborne && !jump_state.jumping {
+        jump_state.since_grounded + delta
+    } else if airborne {
+        f32::INFINITY
+    } else {
+        0.
+    };
+    jump_state.since_pressed = if input.jump_pressed() {
+        0.
+    } else {
+        jump_state.since_pressed + delta
+    };
+    // A jump pressed while crouching stays buffered, in case the player stands up in time
+    if !jump_state.jumping
+        && !crouching
+        && jump_state.since_pressed <= ccs.jump_buffer_time
+        && jump_state.since_grounded <= ccs.coyote_time
+    {
+        jump_state.jumping = true;
+        jump_state.takeoff_height = height;
+        jump_state.since_pressed = f32::INFINITY;
+        jump_state.since_grounded = f32::INFINITY;
+    }
 }
@@ -0,0 +1,120 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use bevy_tnua::{prelude::*, TnuaSystemSet};
+
+use super::{movement::apply_movement, JumpState, PlayerMarker, PlayerNumber, WallState};
+use crate::{
+    config::{CharacterControllerSettings, WallSettings},
+    input::PlayerInputs,
+    states::AppState,
+};
+
+/// The Plugin that lets the player slide down walls and jump off them
+pub struct PlayerWallPlugin;
+
+impl Plugin for PlayerWallPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                detect_walls.before(apply_movement),
+                // Tnua sets the velocity of the player, so these run after it to have the last word
+                (wall_jump, wall_slide)
+                    .chain()
+                    .after(apply_movement)
+                    .after(TnuaSystemSet),
+            )
+                .run_if(in_state(AppState::Playing)),
+        );
+    }
+}
+
+/// This System looks for solid blocks directly beside the collider of the player
+fn detect_walls(
+    rapier_context: Res<RapierContext>,
+    ws: Res<WallSettings>,
+    time: Res<Time>,
+    mut query: Query<(&Transform, &Collider, &mut WallState), With<PlayerMarker>>,
+) {
+    query
+        .iter_mut()
+        .for_each(|(transform, collider, mut wall_state)| {
+            wall_state.lockout = (wall_state.lockout - time.delta_seconds()).max(0.);
+            let position = transform.translation.truncate();
+            let touches = |side: f32| {
+                rapier_context
+                    .cast_shape(
+                        position,
+                        0.,
+                        Vec2::new(side, 0.),
+                        collider,
+                        ShapeCastOptions::with_max_time_of_impact(ws.detection_distance),
+                        QueryFilter::only_fixed().exclude_sensors(),
+                    )
+                    .is_some()
+            };
+            wall_state.side = [-1., 1.].into_iter().find(|side| touches(*side));
+        });
+}
+
+/// This System pushes the player up and away from the wall beside them when jump is pressed in the air
+///
+/// It uses the jump buffer of [JumpState], so a jump pressed just before reaching a wall still counts
+fn wall_jump(
+    ccs: Res<CharacterControllerSettings>,
+    ws: Res<WallSettings>,
+    mut query: Query<
+        (
+            &TnuaController,
+            &mut JumpState,
+            &mut WallState,
+            &mut Velocity,
+        ),
+        With<PlayerMarker>,
+    >,
+) {
+    query.iter_mut().for_each(
+        |(controller, mut jump_state, mut wall_state, mut velocity)| {
+            let Some(side) = wall_state.side else {
+                return;
+            };
+            let airborne = controller.is_airborne().unwrap_or(true);
+            if !airborne || jump_state.jumping || jump_state.since_pressed > ccs.jump_buffer_time {
+                return;
+            }
+            jump_state.since_pressed = f32::INFINITY;
+            wall_state.jump_direction = -side;
+            wall_state.lockout = ws.jump_lockout;
+            velocity.linvel =
+                Vec2::new(-side * ws.jump_angle.cos(), ws.jump_angle.sin()) * ws.jump_speed;
+        },
+    );
+}
+
+/// This System slows down the fall of a player while they press into a wall, and turns them away from it
+fn wall_slide(
+    ws: Res<WallSettings>,
+    inputs: Res<PlayerInputs>,
+    mut query: Query<(
+        &PlayerNumber,
+        &TnuaController,
+        &WallState,
+        &mut Velocity,
+        &mut Sprite,
+    )>,
+) {
+    query.iter_mut().for_each(
+        |(number, controller, wall_state, mut velocity, mut sprite)| {
+            let Some(side) = wall_state.side else {
+                return;
+            };
+            let airborne = controller.is_airborne().unwrap_or(true);
+            let pressing_into_wall = inputs.get(number.0).direction_vector().x * side > 0.;
+            if !airborne || !pressing_into_wall || wall_state.locked_out() {
+                return;
+            }
+            velocity.linvel.y = velocity.linvel.y.max(-ws.slide_speed);
+            sprite.flip_x = side > 0.;
+        },
+    );
+}
@@ -0,0 +1,57 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs::{read_to_string, write};
+use std::io;
+
+use crate::{characters::SelectedCharacter, config::SaveSettings, input::bindings::Bindings};
+
+/// The Plugin that loads the users settings from the settings file
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, load_settings);
+    }
+}
+
+/// The contents of the settings file. Missing entries keep their defaults
+#[derive(Default, Serialize, Deserialize)]
+pub struct SettingsFile {
+    #[serde(default)]
+    pub bindings: Option<Bindings>,
+    /// The id of the character the player plays as
+    #[serde(default)]
+    pub character: Option<String>,
+}
+
+/// Reads the settings file at the given path. Returns the defaults if it is missing or invalid
+pub fn read_settings_file(path: &str) -> SettingsFile {
+    read_to_string(path)
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+/// Changes the settings file at the given path, keeping every entry that is not changed
+pub fn update_settings_file(path: &str, change: impl FnOnce(&mut SettingsFile)) -> io::Result<()> {
+    let mut settings = read_settings_file(path);
+    change(&mut settings);
+    let serialized = serde_json::to_string_pretty(&settings).map_err(io::Error::other)?;
+    write(path, serialized)
+}
+
+/// This System applies the settings file to the resources it configures
+fn load_settings(
+    mut bindings: ResMut<Bindings>,
+    mut selected_character: ResMut<SelectedCharacter>,
+    save_settings: Res<SaveSettings>,
+) {
+    let settings = read_settings_file(&save_settings.settings_path);
+    if let Some(loaded) = settings.bindings {
+        *bindings = loaded;
+        bindings.fill_missing();
+    }
+    if let Some(character) = settings.character {
+        selected_character.0 = character;
+    }
+}
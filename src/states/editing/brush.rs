@@ -0,0 +1,154 @@
+use bevy::prelude::*;
+use std::fmt;
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+use super::{move_block_to_cursor, place_block, HoveringBlock};
+use crate::{
+    camera::CameraMarker,
+    input::PlayerInput,
+    level_management::{execute_level_queues, Level},
+    states::AppState,
+};
+
+/// The Plugin containing the brush shapes and mirrored painting
+pub struct BrushPlugin;
+
+impl Plugin for BrushPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                (cycle_brush, toggle_mirror)
+                    .after(move_block_to_cursor)
+                    .before(place_block)
+                    .before(execute_level_queues),
+                draw_mirror_axis,
+            )
+                .run_if(in_state(AppState::Editing)),
+        );
+    }
+}
+
+/// The radius of the [Brush::Circle] in cells
+const CIRCLE_RADIUS: i32 = 2;
+
+/// The shapes of cells that are painted by a single click
+#[derive(Default, PartialEq, Eq, Clone, Copy, Debug, EnumIter)]
+pub enum Brush {
+    #[default]
+    Single,
+    Square2,
+    Square3,
+    Circle,
+}
+
+impl Brush {
+    /// Returns the cells covered by the brush when it is centered on the given cell
+    pub fn cells(&self, (x, y): (i32, i32)) -> Vec<(i32, i32)> {
+        let (min, max) = match self {
+            Brush::Single => (0, 0),
+            Brush::Square2 => (0, 1),
+            Brush::Square3 => (-1, 1),
+            Brush::Circle => (-CIRCLE_RADIUS, CIRCLE_RADIUS),
+        };
+        (min..=max)
+            .flat_map(|dx| (min..=max).map(move |dy| (dx, dy)))
+            .filter(|(dx, dy)| {
+                *self != Brush::Circle || dx * dx + dy * dy <= CIRCLE_RADIUS * CIRCLE_RADIUS
+            })
+            .map(|(dx, dy)| (x + dx, y - dy))
+            .collect()
+    }
+}
+
+/// A grid line that placements are mirrored across. The line lies on the left or bottom edge of the contained cell
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum MirrorAxis {
+    /// A vertical line, mirroring left and right
+    Vertical(i32),
+    /// A horizontal line, mirroring top and bottom
+    Horizontal(i32),
+}
+
+impl MirrorAxis {
+    /// Returns the mirror image of the given cell
+    pub fn mirror(&self, (x, y): (i32, i32)) -> (i32, i32) {
+        match *self {
+            MirrorAxis::Vertical(line) => (2 * line - 1 - x, y),
+            MirrorAxis::Horizontal(line) => (x, 2 * line - 1 - y),
+        }
+    }
+}
+
+impl fmt::Display for MirrorAxis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MirrorAxis::Vertical(line) => write!(f, "Vertical (x = {})", line),
+            MirrorAxis::Horizontal(line) => write!(f, "Horizontal (y = {})", line),
+        }
+    }
+}
+
+/// This System cycles through the brush shapes when the brush key is pressed
+fn cycle_brush(
+    input: Res<PlayerInput>,
+    mut hovering_block: ResMut<HoveringBlock>,
+    mut level: ResMut<Level>,
+) {
+    if !input.cycle_brush_pressed() {
+        return;
+    }
+    let brushes: Vec<Brush> = Brush::iter().collect();
+    let current = brushes
+        .iter()
+        .position(|brush| *brush == hovering_block.brush)
+        .unwrap_or(0);
+    hovering_block.brush = brushes[(current + 1) % brushes.len()];
+    hovering_block.show_preview(&mut level);
+}
+
+/// This System switches from no mirroring, to mirroring across a vertical line, to a horizontal line.
+/// The line is placed on the grid line closest to the cursor
+fn toggle_mirror(
+    input: Res<PlayerInput>,
+    mut hovering_block: ResMut<HoveringBlock>,
+    mut level: ResMut<Level>,
+) {
+    if !input.toggle_mirror_pressed() {
+        return;
+    }
+    let line = (hovering_block.cursor / 16.).round().as_ivec2();
+    hovering_block.mirror = match hovering_block.mirror {
+        None => Some(MirrorAxis::Vertical(line.x)),
+        Some(MirrorAxis::Vertical(_)) => Some(MirrorAxis::Horizontal(line.y)),
+        Some(MirrorAxis::Horizontal(_)) => None,
+    };
+    hovering_block.show_preview(&mut level);
+}
+
+/// This System draws the line placements are mirrored across
+fn draw_mirror_axis(
+    hovering_block: Res<HoveringBlock>,
+    camera: Query<(&GlobalTransform, &OrthographicProjection), With<CameraMarker>>,
+    mut gizmos: Gizmos,
+) {
+    let Some(mirror) = hovering_block.mirror else {
+        return;
+    };
+    let (transform, projection) = camera.single();
+    let center = transform.translation().truncate();
+    let min = center + projection.area.min;
+    let max = center + projection.area.max;
+    let (start, end) = match mirror {
+        MirrorAxis::Vertical(line) => {
+            let x = line as f32 * 16.;
+            (Vec2::new(x, min.y), Vec2::new(x, max.y))
+        }
+        MirrorAxis::Horizontal(line) => {
+            let y = line as f32 * 16.;
+            (Vec2::new(min.x, y), Vec2::new(max.x, y))
+        }
+    };
+    gizmos.line_2d(start, end, Color::srgb(1., 0.4, 0.8));
+}
@@ -0,0 +1,64 @@
+use bevy::prelude::*;
+
+use super::{edit_without_preview, move_block_to_cursor, HoveringBlock};
+use crate::{
+    input::PlayerInput,
+    level_management::{execute_level_queues, saving::SaveState, BlockChange, Level},
+    states::AppState,
+};
+
+/// The Plugin that allows undoing changes to the blocks of the level
+pub struct HistoryPlugin;
+
+impl Plugin for HistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EditHistory>().add_systems(
+            Update,
+            undo.after(move_block_to_cursor)
+                .before(execute_level_queues)
+                .run_if(in_state(AppState::Editing)),
+        );
+    }
+}
+
+/// How many edits are remembered for undoing
+const MAX_HISTORY: usize = 100;
+
+/// The Resource keeping track of the edits that can be undone, each made of all the blocks it changed
+#[derive(Resource, Default)]
+pub struct EditHistory {
+    edits: Vec<Vec<BlockChange>>,
+}
+
+impl EditHistory {
+    /// Remembers an edit, so it can be undone in one step
+    pub fn record(&mut self, changes: Vec<BlockChange>) {
+        if changes.is_empty() {
+            return;
+        }
+        if self.edits.len() == MAX_HISTORY {
+            self.edits.remove(0);
+        }
+        self.edits.push(changes);
+    }
+}
+
+/// This System reverts the last edit when the undo keybind is pressed
+fn undo(
+    input: Res<PlayerInput>,
+    mut history: ResMut<EditHistory>,
+    mut hovering_block: ResMut<HoveringBlock>,
+    mut level: ResMut<Level>,
+    mut save_state: ResMut<SaveState>,
+) {
+    if !input.undo_pressed() {
+        return;
+    }
+    let Some(changes) = history.edits.pop() else {
+        return;
+    };
+    edit_without_preview(&mut hovering_block, &mut level, |level| {
+        level.apply_changes(&changes, true);
+    });
+    save_state.mark_dirty();
+}
@@ -0,0 +1,203 @@
+use bevy::prelude::*;
+
+use super::{move_block_to_cursor, props::PropEditor, EditorTool, HoveringBlock};
+use crate::{camera::CameraMarker, input::PlayerInput, level_management::Level, states::AppState};
+
+/// The Plugin containing the editors grid overlay and coordinate HUD
+pub struct EditorHudPlugin;
+
+impl Plugin for EditorHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GridOverlay>()
+            .init_resource::<EditorMessage>()
+            .add_systems(OnEnter(AppState::Editing), spawn_hud)
+            .add_systems(OnExit(AppState::Editing), despawn_hud)
+            .add_systems(
+                Update,
+                (
+                    toggle_grid,
+                    draw_grid,
+                    update_hud.after(move_block_to_cursor),
+                )
+                    .run_if(in_state(AppState::Editing)),
+            );
+    }
+}
+
+/// The size of a grid cell in pixels
+const CELL_SIZE: f32 = 16.;
+
+/// Above this many lines per axis, the grid is too dense to be useful and is not drawn
+const MAX_GRID_LINES: i32 = 400;
+
+/// The Resource that keeps track of whether the grid overlay is shown
+#[derive(Resource)]
+pub struct GridOverlay {
+    pub visible: bool,
+}
+
+impl Default for GridOverlay {
+    fn default() -> Self {
+        GridOverlay { visible: true }
+    }
+}
+
+/// How many seconds a message stays in the HUD
+const MESSAGE_DURATION: f32 = 3.;
+
+/// The Resource holding a short message for the user, shown in the HUD for a few seconds
+#[derive(Resource, Default)]
+pub struct EditorMessage {
+    text: String,
+    timer: Timer,
+}
+
+impl EditorMessage {
+    /// Shows the given message in the HUD, replacing the one shown before
+    pub fn show(&mut self, text: impl Into<String>) {
+        self.text = text.into();
+        self.timer = Timer::from_seconds(MESSAGE_DURATION, TimerMode::Once);
+    }
+}
+
+/// Marks the text showing information about the hovered cell
+#[derive(Component)]
+struct HudMarker;
+
+/// This System spawns the HUD text
+fn spawn_hud(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 18.,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.),
+            left: Val::Px(10.),
+            padding: UiRect::all(Val::Px(6.)),
+            ..default()
+        })
+        .with_background_color(Color::srgba(0., 0., 0., 0.5)),
+        HudMarker,
+        Name::new("Editor HUD"),
+    ));
+}
+
+/// This System removes the HUD when leaving [AppState::Editing]
+fn despawn_hud(mut commands: Commands, query: Query<Entity, With<HudMarker>>) {
+    query.iter().for_each(|entity| {
+        commands.entity(entity).despawn_recursive();
+    });
+}
+
+/// This System shows or hides the grid overlay
+fn toggle_grid(input: Res<PlayerInput>, mut grid_overlay: ResMut<GridOverlay>) {
+    if input.toggle_grid_pressed() {
+        grid_overlay.visible = !grid_overlay.visible;
+    }
+}
+
+/// This System draws the grid lines in the area seen by the camera
+fn draw_grid(
+    grid_overlay: Res<GridOverlay>,
+    camera: Query<(&GlobalTransform, &OrthographicProjection), With<CameraMarker>>,
+    mut gizmos: Gizmos,
+) {
+    if !grid_overlay.visible {
+        return;
+    }
+    let (transform, projection) = camera.single();
+    let center = transform.translation().truncate();
+    let min = center + projection.area.min;
+    let max = center + projection.area.max;
+    let first = (min / CELL_SIZE).floor().as_ivec2();
+    let last = (max / CELL_SIZE).ceil().as_ivec2();
+    if last.x - first.x > MAX_GRID_LINES || last.y - first.y > MAX_GRID_LINES {
+        return;
+    }
+    let color = Color::srgba(1., 1., 1., 0.15);
+    (first.x..=last.x).for_each(|x| {
+        let x = x as f32 * CELL_SIZE;
+        gizmos.line_2d(Vec2::new(x, min.y), Vec2::new(x, max.y), color);
+    });
+    (first.y..=last.y).for_each(|y| {
+        let y = y as f32 * CELL_SIZE;
+        gizmos.line_2d(Vec2::new(min.x, y), Vec2::new(max.x, y), color);
+    });
+}
+
+/// This System shows the coordinates of the cursor, information about the hovered block and the latest [EditorMessage]
+fn update_hud(
+    time: Res<Time>,
+    hovering_block: Res<HoveringBlock>,
+    prop_editor: Res<PropEditor>,
+    mut message: ResMut<EditorMessage>,
+    level: Res<Level>,
+    mut query: Query<&mut Text, With<HudMarker>>,
+) {
+    message.timer.tick(time.delta());
+    let position = hovering_block.hovering;
+    let layer = hovering_block.layer;
+    // The hovered cells hold the preview of the selected material, so the level is read with the blocks the preview replaced
+    let level_layer = level.layer(layer);
+    let preview = &hovering_block.preview;
+    let block = match hovering_block.original_material {
+        Some(material) => format!(
+            "{:?} ({:?}, {:?})",
+            material,
+            level_layer.get_row_with(position, preview),
+            level_layer.get_column_with(position, preview)
+        ),
+        None => "None".to_string(),
+    };
+    let previewed = preview
+        .keys()
+        .filter(|position| level_layer.material_map.contains_key(position))
+        .count();
+    let replaced = preview
+        .values()
+        .filter(|material| material.is_some())
+        .count();
+    let block_count = level.block_count() + replaced - previewed;
+
+    let tool = match hovering_block.tool {
+        EditorTool::Tiles => "Tiles".to_string(),
+        EditorTool::Terrain => "Terrain".to_string(),
+        EditorTool::Props => format!(
+            "Props ({})",
+            prop_editor
+                .current_image()
+                .map_or("none found", String::as_str)
+        ),
+    };
+    let mirror = match hovering_block.mirror {
+        Some(mirror) => mirror.to_string(),
+        None => "Off".to_string(),
+    };
+    let cursor = hovering_block.cursor;
+    let mut value = format!(
+        "Cell: ({}, {})\nWorld: ({:.1}, {:.1})\nTool: {}\nBrush: {:?}\nMirror: {}\nLayer: {:?}\nBlock: {}\nBlocks: {}\nProps: {}",
+        position.0,
+        position.1,
+        cursor.x,
+        cursor.y,
+        tool,
+        hovering_block.brush,
+        mirror,
+        layer,
+        block,
+        block_count,
+        level.props.props.len()
+    );
+    if !message.timer.finished() {
+        value = format!("{}\n\n{}", value, message.text);
+    }
+    query.iter_mut().for_each(|mut text| {
+        text.sections[0].value.clone_from(&value);
+    });
+}
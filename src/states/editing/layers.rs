@@ -0,0 +1,279 @@
+use bevy::prelude::*;
+use std::collections::HashMap;
+use strum::IntoEnumIterator;
+
+use super::HoveringBlock;
+use crate::{
+    input::PlayerInput,
+    level_management::{execute_level_queues, Layer, Level},
+    states::AppState,
+};
+
+/// The Plugin containing the editors layer panel
+pub struct LayerPanelPlugin;
+
+impl Plugin for LayerPanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LayerVisibility>()
+            .add_systems(
+                OnEnter(AppState::Editing),
+                (spawn_layer_panel, apply_layer_visibility),
+            )
+            .add_systems(
+                OnExit(AppState::Editing),
+                (despawn_layer_panel, show_all_layers),
+            )
+            .add_systems(
+                Update,
+                (
+                    (select_layer_by_click, select_layer_by_key).before(execute_level_queues),
+                    toggle_layer_visibility,
+                    apply_layer_visibility.run_if(resource_changed::<LayerVisibility>),
+                    hide_new_blocks,
+                    update_layer_panel,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::Editing)),
+            );
+    }
+}
+
+/// The Resource keeping track of which layers are shown in the editor
+#[derive(Resource)]
+pub struct LayerVisibility(pub HashMap<Layer, bool>);
+
+impl Default for LayerVisibility {
+    fn default() -> Self {
+        LayerVisibility(Layer::iter().map(|layer| (layer, true)).collect())
+    }
+}
+
+impl LayerVisibility {
+    /// Returns whether the given [Layer] is shown
+    pub fn is_visible(&self, layer: Layer) -> bool {
+        *self.0.get(&layer).unwrap_or(&true)
+    }
+}
+
+/// Marks the root node of the layer panel
+#[derive(Component)]
+struct LayerPanelMarker;
+
+/// A button that makes its [Layer] the one being edited
+#[derive(Component, Clone, Copy)]
+struct LayerSelectButton(Layer);
+
+/// A button that shows or hides its [Layer]
+#[derive(Component, Clone, Copy)]
+struct LayerVisibilityButton(Layer);
+
+/// Helper function that creates the label of a button
+fn button_text(value: &str) -> TextBundle {
+    TextBundle::from_section(
+        value,
+        TextStyle {
+            font_size: 18.,
+            color: Color::WHITE,
+            ..default()
+        },
+    )
+}
+
+/// Makes another [Layer] the one being edited, moving the hover preview over to it
+pub fn switch_layer(hovering_block: &mut HoveringBlock, level: &mut Level, layer: Layer) {
+    if hovering_block.layer == layer {
+        return;
+    }
+    hovering_block.clear_preview(level);
+    hovering_block.layer = layer;
+    hovering_block.show_preview(level);
+}
+
+/// This System spawns the layer panel, with a row per [Layer] from front to back
+fn spawn_layer_panel(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    right: Val::Px(10.),
+                    bottom: Val::Px(10.),
+                    padding: UiRect::all(Val::Px(4.)),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(4.),
+                    ..default()
+                },
+                background_color: Color::srgba(0., 0., 0., 0.5).into(),
+                ..default()
+            },
+            Interaction::default(),
+            LayerPanelMarker,
+            Name::new("Layer Panel"),
+        ))
+        .with_children(|parent| {
+            Layer::iter().rev().for_each(|layer| {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            column_gap: Val::Px(4.),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        row.spawn((
+                            ButtonBundle {
+                                style: Style {
+                                    width: Val::Px(110.),
+                                    padding: UiRect::all(Val::Px(4.)),
+                                    border: UiRect::all(Val::Px(2.)),
+                                    ..default()
+                                },
+                                background_color: Color::srgba(0., 0., 0., 0.3).into(),
+                                ..default()
+                            },
+                            LayerSelectButton(layer),
+                        ))
+                        .with_children(|button| {
+                            button.spawn(button_text(layer.as_ref()));
+                        });
+                        row.spawn((
+                            ButtonBundle {
+                                style: Style {
+                                    width: Val::Px(70.),
+                                    padding: UiRect::all(Val::Px(4.)),
+                                    border: UiRect::all(Val::Px(2.)),
+                                    ..default()
+                                },
+                                background_color: Color::srgba(0., 0., 0., 0.3).into(),
+                                ..default()
+                            },
+                            LayerVisibilityButton(layer),
+                        ))
+                        .with_children(|button| {
+                            button.spawn(button_text(""));
+                        });
+                    });
+            });
+        });
+}
+
+/// This System removes the layer panel when leaving [AppState::Editing]
+fn despawn_layer_panel(mut commands: Commands, query: Query<Entity, With<LayerPanelMarker>>) {
+    query.iter().for_each(|entity| {
+        commands.entity(entity).despawn_recursive();
+    });
+}
+
+/// This System selects the [Layer] whose button was clicked
+fn select_layer_by_click(
+    query: Query<(&Interaction, &LayerSelectButton), Changed<Interaction>>,
+    mut hovering_block: ResMut<HoveringBlock>,
+    mut level: ResMut<Level>,
+) {
+    query.iter().for_each(|(interaction, button)| {
+        if *interaction == Interaction::Pressed {
+            switch_layer(&mut hovering_block, &mut level, button.0);
+        }
+    });
+}
+
+/// This System cycles through the layers when the layer key is pressed
+fn select_layer_by_key(
+    input: Res<PlayerInput>,
+    mut hovering_block: ResMut<HoveringBlock>,
+    mut level: ResMut<Level>,
+) {
+    if !input.cycle_layer_pressed() {
+        return;
+    }
+    let layers: Vec<Layer> = Layer::iter().collect();
+    let current = layers
+        .iter()
+        .position(|layer| *layer == hovering_block.layer)
+        .unwrap_or(0);
+    let next = layers[(current + 1) % layers.len()];
+    switch_layer(&mut hovering_block, &mut level, next);
+}
+
+/// This System shows or hides the [Layer] whose visibility button was clicked
+fn toggle_layer_visibility(
+    query: Query<(&Interaction, &LayerVisibilityButton), Changed<Interaction>>,
+    mut layer_visibility: ResMut<LayerVisibility>,
+) {
+    query.iter().for_each(|(interaction, button)| {
+        if *interaction == Interaction::Pressed {
+            let visible = layer_visibility.is_visible(button.0);
+            layer_visibility.0.insert(button.0, !visible);
+        }
+    });
+}
+
+/// Helper function that returns the [Visibility] blocks of a layer should have
+fn visibility_of(layer_visibility: &LayerVisibility, layer: Layer) -> Visibility {
+    if layer_visibility.is_visible(layer) {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    }
+}
+
+/// This System shows or hides all blocks according to the [LayerVisibility]
+fn apply_layer_visibility(
+    layer_visibility: Res<LayerVisibility>,
+    mut query: Query<(&Layer, &mut Visibility)>,
+) {
+    query.iter_mut().for_each(|(layer, mut visibility)| {
+        *visibility = visibility_of(&layer_visibility, *layer);
+    });
+}
+
+/// This System hides blocks that were just placed on a hidden [Layer]
+fn hide_new_blocks(
+    layer_visibility: Res<LayerVisibility>,
+    mut query: Query<(&Layer, &mut Visibility), Added<Layer>>,
+) {
+    query.iter_mut().for_each(|(layer, mut visibility)| {
+        *visibility = visibility_of(&layer_visibility, *layer);
+    });
+}
+
+/// This System shows every block again when leaving [AppState::Editing]
+fn show_all_layers(mut query: Query<&mut Visibility, With<Layer>>) {
+    query.iter_mut().for_each(|mut visibility| {
+        *visibility = Visibility::Inherited;
+    });
+}
+
+/// This System outlines the [Layer] being edited and labels the visibility buttons
+fn update_layer_panel(
+    hovering_block: Res<HoveringBlock>,
+    layer_visibility: Res<LayerVisibility>,
+    mut select_buttons: Query<(&LayerSelectButton, &mut BorderColor)>,
+    visibility_buttons: Query<(&LayerVisibilityButton, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    select_buttons
+        .iter_mut()
+        .for_each(|(button, mut border_color)| {
+            *border_color = if button.0 == hovering_block.layer {
+                Color::WHITE.into()
+            } else {
+                Color::NONE.into()
+            };
+        });
+    visibility_buttons.iter().for_each(|(button, children)| {
+        let label = if layer_visibility.is_visible(button.0) {
+            "Shown"
+        } else {
+            "Hidden"
+        };
+        children.iter().for_each(|&child| {
+            if let Ok(mut text) = texts.get_mut(child) {
+                if text.sections[0].value != label {
+                    text.sections[0].value = label.to_string();
+                }
+            }
+        });
+    });
+}
@@ -0,0 +1,114 @@
+use bevy::prelude::*;
+
+use super::{cursor_not_over_ui, move_block_to_cursor, place_block, HoveringBlock};
+use crate::{
+    config::LevelSettings, input::PlayerInput, level_management::saving::SaveState,
+    states::AppState,
+};
+
+/// The Plugin that allows dragging the spawn indicator and the death line around
+pub struct MarkerDragPlugin;
+
+impl Plugin for MarkerDragPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Dragging>().add_systems(
+            Update,
+            (start_drag.run_if(cursor_not_over_ui), drag_marker)
+                .chain()
+                .after(move_block_to_cursor)
+                .before(place_block)
+                .run_if(in_state(AppState::Editing)),
+        );
+    }
+}
+
+/// Marks the sprite showing where the player spawns
+#[derive(Component)]
+pub struct SpawnIndicatorMarker;
+
+/// Marks the line below which the player dies
+#[derive(Component)]
+pub struct DeathLineMarker;
+
+/// How close to the death line a click has to be to grab it
+const DEATH_LINE_GRAB_DISTANCE: f32 = 8.;
+
+/// The Resource keeping track of which marker is currently being dragged
+#[derive(Resource, Default, PartialEq, Eq)]
+pub enum Dragging {
+    #[default]
+    Nothing,
+    SpawnIndicator,
+    DeathLine,
+}
+
+/// Run condition that is false while a marker is being dragged
+pub fn not_dragging(dragging: Res<Dragging>) -> bool {
+    *dragging == Dragging::Nothing
+}
+
+/// This System grabs a marker, if it was clicked
+fn start_drag(
+    input: Res<PlayerInput>,
+    hovering_block: Res<HoveringBlock>,
+    ls: Res<LevelSettings>,
+    mut dragging: ResMut<Dragging>,
+) {
+    if !input.left_clicked() {
+        return;
+    }
+    let cursor = hovering_block.cursor;
+    let spawn = ls.spawn_location.translation.truncate();
+    if (cursor - spawn).abs().max_element() <= 16. {
+        *dragging = Dragging::SpawnIndicator;
+    } else if (cursor.y - ls.death_height).abs() <= DEATH_LINE_GRAB_DISTANCE {
+        *dragging = Dragging::DeathLine;
+    }
+}
+
+/// This System moves the grabbed marker to the hovered grid cell and releases it once the mouse button is let go
+#[allow(clippy::too_many_arguments)]
+fn drag_marker(
+    input: Res<PlayerInput>,
+    hovering_block: Res<HoveringBlock>,
+    mut ls: ResMut<LevelSettings>,
+    mut dragging: ResMut<Dragging>,
+    mut save_state: ResMut<SaveState>,
+    mut spawn_indicator: Query<
+        &mut Transform,
+        (With<SpawnIndicatorMarker>, Without<DeathLineMarker>),
+    >,
+    mut death_line: Query<&mut Transform, (With<DeathLineMarker>, Without<SpawnIndicatorMarker>)>,
+) {
+    if *dragging == Dragging::Nothing {
+        return;
+    }
+    if !input.left_click_held() {
+        *dragging = Dragging::Nothing;
+        return;
+    }
+    let (x, y) = hovering_block.hovering;
+    match *dragging {
+        Dragging::Nothing => {}
+        Dragging::SpawnIndicator => {
+            let position = Vec2::new(x as f32 * 16. + 8., y as f32 * 16. + 8.);
+            if ls.spawn_location.translation.truncate() != position {
+                ls.spawn_location.translation = position.extend(ls.spawn_location.translation.z);
+                save_state.mark_dirty();
+                spawn_indicator.iter_mut().for_each(|mut transform| {
+                    transform.translation = ls.spawn_location.translation;
+                });
+            }
+        }
+        Dragging::DeathLine => {
+            let height = y as f32 * 16.;
+            if ls.death_height != height {
+                ls.death_height = height;
+                save_state.mark_dirty();
+                death_line.iter_mut().for_each(|mut transform| {
+                    transform.translation.y = ls.death_height;
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,399 @@
+mod brush;
+mod history;
+mod hud;
+pub mod layers;
+mod markers;
+mod palette;
+pub mod playtest;
+mod props;
+mod replace;
+pub mod selection;
+mod terrain;
+
+use std::path::Path;
+
+use super::AppState;
+use crate::input::PlayerInput;
+use crate::level_management::{
+    execute_level_queues, props::Prop, saving::SaveState, BlockChange, Layer, Level,
+};
+use crate::{
+    camera::{movement::MovementMode, CameraMarker},
+    config::LevelSettings,
+    utilities::assets::{ColorResource, Material},
+};
+use bevy::{prelude::*, sprite::Mesh2dHandle};
+use brush::{Brush, BrushPlugin, MirrorAxis};
+use history::{EditHistory, HistoryPlugin};
+use hud::EditorHudPlugin;
+use layers::LayerPanelPlugin;
+use markers::{not_dragging, DeathLineMarker, MarkerDragPlugin, SpawnIndicatorMarker};
+use palette::PalettePlugin;
+use playtest::PlaytestPlugin;
+use props::PropEditorPlugin;
+use replace::ReplacePlugin;
+use selection::SelectionPlugin;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+use terrain::TerrainPlugin;
+
+pub struct EditingPlugin;
+
+impl Plugin for EditingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::Editing), enter_editing)
+            .add_systems(OnExit(AppState::Editing), exit_editing)
+            .add_systems(
+                Update,
+                (
+                    cycle_tool,
+                    move_block_to_cursor,
+                    place_block
+                        .run_if(tool_is(EditorTool::Tiles))
+                        .run_if(cursor_not_over_ui)
+                        .run_if(not_dragging),
+                )
+                    .chain()
+                    .before(execute_level_queues)
+                    .run_if(in_state(AppState::Editing)),
+            )
+            .add_plugins((
+                PalettePlugin,
+                MarkerDragPlugin,
+                PlaytestPlugin,
+                EditorHudPlugin,
+                LayerPanelPlugin,
+                PropEditorPlugin,
+                HistoryPlugin,
+                SelectionPlugin,
+                ReplacePlugin,
+                BrushPlugin,
+                TerrainPlugin,
+            ))
+            .insert_resource(HoveringBlock::default());
+    }
+}
+
+#[derive(Resource)]
+pub struct HoveringBlock {
+    pub last_hovered: (i32, i32),
+    pub hovering: (i32, i32),
+    pub cursor: Vec2,
+    pub selected_material: Option<Material>,
+    pub original_material: Option<Material>,
+    /// The [Layer] that is being edited
+    pub layer: Layer,
+    /// What clicking in the editor does
+    pub tool: EditorTool,
+    /// The shape of the cells painted by a click
+    pub brush: Brush,
+    /// The grid line that placements are mirrored across, if any
+    pub mirror: Option<MirrorAxis>,
+    /// The cells showing the hover preview, with the materials they really contain
+    pub preview: HashMap<(i32, i32), Option<Material>>,
+}
+
+/// The tools of the editor, which decide what clicking does
+#[derive(Default, PartialEq, Eq, Clone, Copy, Debug, EnumIter)]
+pub enum EditorTool {
+    /// Places the selected material on the grid
+    #[default]
+    Tiles,
+    /// Places, selects and moves freely placed props
+    Props,
+    /// Raises and lowers the ground column by column, and carves caves into the selected region
+    Terrain,
+}
+
+impl HoveringBlock {
+    /// Returns every cell a click would paint: the brush around the hovered cell and its mirror image.
+    /// Only the hovered cell is affected by tools other than [EditorTool::Tiles]
+    pub fn footprint(&self) -> Vec<(i32, i32)> {
+        if self.tool != EditorTool::Tiles {
+            return vec![self.hovering];
+        }
+        let mut cells = self.brush.cells(self.hovering);
+        if let Some(mirror) = self.mirror {
+            let mirrored: Vec<(i32, i32)> = cells.iter().map(|&cell| mirror.mirror(cell)).collect();
+            cells.extend(mirrored);
+        }
+        cells.sort();
+        cells.dedup();
+        cells
+    }
+
+    /// Puts the real blocks back in place of the hover preview
+    pub fn clear_preview(&mut self, level: &mut Level) {
+        let level = level.layer_mut(self.layer);
+        self.preview
+            .drain()
+            .for_each(|(position, original)| level.insert(position, original));
+    }
+
+    /// Shows the selected material on every cell of the [footprint](Self::footprint), replacing the previous preview
+    pub fn show_preview(&mut self, level: &mut Level) {
+        self.clear_preview(level);
+        let footprint = self.footprint();
+        let level = level.layer_mut(self.layer);
+        footprint.into_iter().for_each(|position| {
+            self.preview
+                .insert(position, level.material_map.get(&position).copied());
+            if self.tool == EditorTool::Tiles {
+                level.insert(position, self.selected_material);
+            }
+        });
+        self.original_material = self.preview.get(&self.hovering).copied().flatten();
+    }
+}
+
+/// Run condition that is true while the given [EditorTool] is in use
+pub fn tool_is(tool: EditorTool) -> impl Fn(Res<HoveringBlock>) -> bool {
+    move |hovering_block: Res<HoveringBlock>| hovering_block.tool == tool
+}
+
+/// Switches to another [EditorTool], updating the hover preview
+pub fn switch_tool(hovering_block: &mut HoveringBlock, level: &mut Level, tool: EditorTool) {
+    hovering_block.clear_preview(level);
+    hovering_block.tool = tool;
+    hovering_block.show_preview(level);
+}
+
+/// Runs an edit on the level with the hover preview taken out, so the edit sees and changes the real blocks
+pub fn edit_without_preview<T>(
+    hovering_block: &mut HoveringBlock,
+    level: &mut Level,
+    edit: impl FnOnce(&mut Level) -> T,
+) -> T {
+    hovering_block.clear_preview(level);
+    let result = edit(level);
+    hovering_block.show_preview(level);
+    result
+}
+
+/// This System cycles through the tools when the tool key is pressed
+fn cycle_tool(
+    input: Res<PlayerInput>,
+    mut hovering_block: ResMut<HoveringBlock>,
+    mut level: ResMut<Level>,
+) {
+    if !input.cycle_tool_pressed() {
+        return;
+    }
+    let tools: Vec<EditorTool> = EditorTool::iter().collect();
+    let current = tools
+        .iter()
+        .position(|tool| *tool == hovering_block.tool)
+        .unwrap_or(0);
+    let next = tools[(current + 1) % tools.len()];
+    switch_tool(&mut hovering_block, &mut level, next);
+}
+
+impl Default for HoveringBlock {
+    fn default() -> Self {
+        HoveringBlock {
+            last_hovered: (0, 0),
+            hovering: (0, 0),
+            cursor: Vec2::ZERO,
+            selected_material: Some(Material::GRASS_GREEN),
+            original_material: None,
+            layer: Layer::Solid,
+            tool: EditorTool::Tiles,
+            brush: Brush::default(),
+            mirror: None,
+            preview: HashMap::new(),
+        }
+    }
+}
+
+fn move_block_to_cursor(
+    camera_query: Query<(&Camera, &GlobalTransform), With<CameraMarker>>,
+    windows: Query<&Window>,
+    mut hovering_block: ResMut<HoveringBlock>,
+    mut level: ResMut<Level>,
+) {
+    let (camera, camera_transform) = camera_query.single();
+    let Some(cursor_position) = windows.single().cursor_position() else {
+        return;
+    };
+
+    let Some(point) = camera.viewport_to_world_2d(camera_transform, cursor_position) else {
+        return;
+    };
+
+    hovering_block.cursor = point;
+
+    let x = (point.x as f32 / 16.0).floor() as i32;
+    let y = (point.y as f32 / 16.0).floor() as i32;
+
+    let grid_point = (x, y);
+
+    // Check if the cursor has moved to a new block, or if there is no preview yet, like right after entering the editor.
+    if grid_point != hovering_block.hovering || hovering_block.preview.is_empty() {
+        // Update the `last_hovered` to the new hovered block.
+        hovering_block.last_hovered = hovering_block.hovering;
+
+        // Update the `hovering` position to the new block.
+        hovering_block.hovering = grid_point;
+
+        // Restore the original materials under the last preview, then temporarily set every cell of the
+        // new footprint to the selected material (which could be `None`).
+        hovering_block.show_preview(&mut level);
+    }
+}
+
+fn place_block(
+    input: Res<PlayerInput>,
+    mut level: ResMut<Level>,
+    mut hovering_block: ResMut<HoveringBlock>,
+    mut save_state: ResMut<SaveState>,
+    mut history: ResMut<EditHistory>,
+) {
+    if !input.left_clicked() {
+        return;
+    }
+    let layer = hovering_block.layer;
+    let selected = hovering_block.selected_material;
+    let changes: Vec<BlockChange> = hovering_block
+        .preview
+        .iter()
+        .filter(|(_, &original)| original != selected)
+        .map(|(&position, &original)| BlockChange {
+            layer,
+            position,
+            before: original,
+            after: selected,
+        })
+        .collect();
+    if changes.is_empty() {
+        return;
+    }
+    changes.iter().for_each(|change| {
+        level.layer_mut(layer).set_block(change.position, selected);
+    });
+    hovering_block
+        .preview
+        .values_mut()
+        .for_each(|original| *original = selected);
+    hovering_block.original_material = selected;
+    save_state.mark_dirty();
+    history.record(changes);
+}
+
+/// Run condition that is false while the cursor is over an interactive UI node
+pub fn cursor_not_over_ui(interactions: Query<&Interaction>) -> bool {
+    interactions
+        .iter()
+        .all(|interaction| *interaction == Interaction::None)
+}
+
+fn enter_editing(
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut query: Query<&mut MovementMode, With<CameraMarker>>,
+    mut commands: Commands,
+    ls: Res<LevelSettings>,
+    materials: Res<ColorResource>,
+    asset_server: Res<AssetServer>,
+) {
+    query.iter_mut().for_each(|mut movement_mode| {
+        *movement_mode = MovementMode::Input;
+    });
+    let character_handle = asset_server.load(Path::new("CHARACTER.png"));
+    commands.spawn((
+        SpriteBundle {
+            texture: character_handle,
+            transform: ls.spawn_location,
+            ..default()
+        },
+        SpawnIndicatorMarker,
+    ));
+    let mesh: Mesh2dHandle = meshes.add(Rectangle::new(10000000., 10.)).into();
+    commands.spawn((
+        ColorMesh2dBundle {
+            mesh,
+            material: materials.0[1].0.clone(),
+            transform: Transform::from_xyz(0., ls.death_height, -10.),
+            ..default()
+        },
+        DeathLineMarker,
+    ));
+}
+
+mod tuple_map {
+    use super::*;
+    use serde::{Deserializer, Serializer};
+    use std::collections::HashMap;
+
+    pub fn serialize<S>(
+        map: &HashMap<(i32, i32), Material>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let string_map: HashMap<String, &Material> = map
+            .iter()
+            .map(|(k, v)| (format!("{},{}", k.0, k.1), v))
+            .collect();
+        string_map.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<HashMap<(i32, i32), Material>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let string_map: HashMap<String, Material> = HashMap::deserialize(deserializer)?;
+        let map = string_map
+            .into_iter()
+            .map(|(k, v)| {
+                let mut split = k.split(',');
+                let x = split
+                    .next()
+                    .and_then(|x| x.parse::<i32>().ok())
+                    .ok_or_else(|| serde::de::Error::custom("Invalid key format"))?;
+                let y = split
+                    .next()
+                    .and_then(|y| y.parse::<i32>().ok())
+                    .ok_or_else(|| serde::de::Error::custom("Invalid key format"))?;
+                Ok(((x, y), v))
+            })
+            .collect::<Result<HashMap<(i32, i32), Material>, _>>()?;
+        Ok(map)
+    }
+}
+
+/// The blocks of a single [Layer], in a serializable form
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LayerMap(#[serde(with = "tuple_map")] pub HashMap<(i32, i32), Material>);
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SerdeMapContainer {
+    /// The blocks of [Layer::Solid]. Kept separate, so older level files stay loadable
+    #[serde(with = "tuple_map")]
+    pub map: HashMap<(i32, i32), Material>,
+    /// The blocks of every other [Layer]
+    #[serde(default)]
+    pub layers: HashMap<Layer, LayerMap>,
+    /// The freely placed decorative sprites
+    #[serde(default)]
+    pub props: Vec<Prop>,
+    #[serde(default)]
+    pub spawn_location: Option<(f32, f32)>,
+    #[serde(default)]
+    pub death_height: Option<f32>,
+    #[serde(default)]
+    pub dash_cooldown: Option<f32>,
+}
+
+fn exit_editing(
+    spawn_indicator: Query<Entity, With<SpawnIndicatorMarker>>,
+    death_marker: Query<Entity, With<DeathLineMarker>>,
+    mut commands: Commands,
+    mut hovering_block: ResMut<HoveringBlock>,
+    mut level: ResMut<Level>,
+) {
+    commands.entity(spawn_indicator.single()).despawn();
+    commands.entity(death_marker.single()).despawn();
+    hovering_block.clear_preview(&mut level);
+}
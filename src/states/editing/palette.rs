@@ -0,0 +1,217 @@
+use bevy::prelude::*;
+use strum::IntoEnumIterator;
+
+use super::{cursor_not_over_ui, switch_tool, EditorTool, HoveringBlock};
+use crate::{
+    input::{bindings::PALETTE_ACTIONS, ctrl_held, PlayerInput},
+    level_management::{execute_level_queues, Level},
+    states::AppState,
+    utilities::assets::{Column, ImageHandles, Material, Row},
+};
+
+/// The Plugin containing the material palette shown while editing
+pub struct PalettePlugin;
+
+impl Plugin for PalettePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::Editing), spawn_palette)
+            .add_systems(OnExit(AppState::Editing), despawn_palette)
+            .add_systems(
+                Update,
+                (
+                    select_by_click,
+                    select_by_key,
+                    select_by_scroll.run_if(ctrl_held.or_else(not(cursor_not_over_ui))),
+                    highlight_selected_slot,
+                )
+                    .chain()
+                    .before(execute_level_queues)
+                    .run_if(in_state(AppState::Editing)),
+            );
+    }
+}
+
+/// Marks the root node of the palette
+#[derive(Component)]
+struct PaletteMarker;
+
+/// A slot of the palette. `None` is the eraser
+#[derive(Component, Clone, Copy)]
+struct PaletteSlot(Option<Material>);
+
+/// Returns every palette entry in display order. The eraser comes last
+pub fn palette_entries() -> Vec<Option<Material>> {
+    Material::iter().map(Some).chain([None]).collect()
+}
+
+/// Changes the selected material and switches to placing tiles with it
+fn select(hovering_block: &mut HoveringBlock, level: &mut Level, material: Option<Material>) {
+    hovering_block.selected_material = material;
+    switch_tool(hovering_block, level, EditorTool::Tiles);
+}
+
+/// This System spawns the palette, with one slot per [Material] and an eraser
+///
+/// Slots beyond the ones of [PALETTE_ACTIONS] can't be selected with a key, which is shown on them
+fn spawn_palette(mut commands: Commands, image_handles: Res<ImageHandles>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(10.),
+                    bottom: Val::Px(10.),
+                    padding: UiRect::all(Val::Px(4.)),
+                    column_gap: Val::Px(4.),
+                    ..default()
+                },
+                background_color: Color::srgba(0., 0., 0., 0.5).into(),
+                ..default()
+            },
+            Interaction::default(),
+            PaletteMarker,
+            Name::new("Palette"),
+        ))
+        .with_children(|parent| {
+            palette_entries()
+                .into_iter()
+                .enumerate()
+                .for_each(|(index, entry)| {
+                    parent
+                        .spawn((
+                            ButtonBundle {
+                                style: Style {
+                                    width: Val::Px(40.),
+                                    height: Val::Px(40.),
+                                    border: UiRect::all(Val::Px(2.)),
+                                    justify_content: JustifyContent::Center,
+                                    align_items: AlignItems::Center,
+                                    ..default()
+                                },
+                                background_color: Color::srgba(0., 0., 0., 0.3).into(),
+                                ..default()
+                            },
+                            PaletteSlot(entry),
+                        ))
+                        .with_children(|slot| {
+                            match entry {
+                                Some(material) => {
+                                    let texture = image_handles
+                                        .0
+                                        .get(&(material, Row::TOP, Column::LEFT))
+                                        .unwrap()
+                                        .clone();
+                                    slot.spawn(ImageBundle {
+                                        image: UiImage::new(texture),
+                                        style: Style {
+                                            width: Val::Px(32.),
+                                            height: Val::Px(32.),
+                                            ..default()
+                                        },
+                                        ..default()
+                                    });
+                                }
+                                None => {
+                                    slot.spawn(TextBundle::from_section(
+                                        "X",
+                                        TextStyle {
+                                            font_size: 28.,
+                                            color: Color::srgb(0.9, 0.2, 0.2),
+                                            ..default()
+                                        },
+                                    ));
+                                }
+                            }
+                            // Spawned last, so it is drawn over the image
+                            if index >= PALETTE_ACTIONS.len() {
+                                slot.spawn(
+                                    TextBundle::from_section(
+                                        "no key",
+                                        TextStyle {
+                                            font_size: 10.,
+                                            color: Color::srgb(0.9, 0.9, 0.9),
+                                            ..default()
+                                        },
+                                    )
+                                    .with_style(Style {
+                                        position_type: PositionType::Absolute,
+                                        bottom: Val::Px(0.),
+                                        ..default()
+                                    }),
+                                );
+                            }
+                        });
+                });
+        });
+}
+
+/// This System removes the palette when leaving [AppState::Editing]
+fn despawn_palette(mut commands: Commands, query: Query<Entity, With<PaletteMarker>>) {
+    query.iter().for_each(|entity| {
+        commands.entity(entity).despawn_recursive();
+    });
+}
+
+/// This System selects the slot that was clicked
+fn select_by_click(
+    query: Query<(&Interaction, &PaletteSlot), Changed<Interaction>>,
+    mut hovering_block: ResMut<HoveringBlock>,
+    mut level: ResMut<Level>,
+) {
+    query.iter().for_each(|(interaction, slot)| {
+        if *interaction == Interaction::Pressed {
+            select(&mut hovering_block, &mut level, slot.0);
+        }
+    });
+}
+
+/// This System selects the slot matching the pressed number key
+fn select_by_key(
+    input: Res<PlayerInput>,
+    mut hovering_block: ResMut<HoveringBlock>,
+    mut level: ResMut<Level>,
+) {
+    let Some(index) = input.palette_slot_pressed() else {
+        return;
+    };
+    if let Some(&entry) = palette_entries().get(index) {
+        select(&mut hovering_block, &mut level, entry);
+    }
+}
+
+/// This System cycles through the slots with the mouse wheel, while over the UI or holding control
+fn select_by_scroll(
+    input: Res<PlayerInput>,
+    mut hovering_block: ResMut<HoveringBlock>,
+    mut level: ResMut<Level>,
+) {
+    let scroll = input.scroll();
+    if scroll == 0. {
+        return;
+    }
+    let entries = palette_entries();
+    let current = entries
+        .iter()
+        .position(|entry| *entry == hovering_block.selected_material)
+        .unwrap_or(0);
+    let next = if scroll > 0. {
+        (current + entries.len() - 1) % entries.len()
+    } else {
+        (current + 1) % entries.len()
+    };
+    select(&mut hovering_block, &mut level, entries[next]);
+}
+
+/// This System outlines the slot of the currently selected material
+fn highlight_selected_slot(
+    hovering_block: Res<HoveringBlock>,
+    mut query: Query<(&PaletteSlot, &mut BorderColor)>,
+) {
+    query.iter_mut().for_each(|(slot, mut border_color)| {
+        *border_color = if slot.0 == hovering_block.selected_material {
+            Color::WHITE.into()
+        } else {
+            Color::NONE.into()
+        };
+    });
+}
@@ -0,0 +1,86 @@
+use bevy::prelude::*;
+
+use super::{cursor_not_over_ui, HoveringBlock};
+use crate::{
+    camera::CameraMarker,
+    config::{CameraSettings, LevelSettings},
+    input::PlayerInput,
+    states::AppState,
+    utilities::easing::{EasingFunction, EasingType, TimeEase},
+};
+
+/// The Plugin that allows play-testing the level from any position
+pub struct PlaytestPlugin;
+
+impl Plugin for PlaytestPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Playtest>()
+            .add_systems(
+                Update,
+                play_from_here
+                    .run_if(cursor_not_over_ui)
+                    .run_if(in_state(AppState::Editing)),
+            )
+            .add_systems(OnEnter(AppState::Editing), end_playtest);
+    }
+}
+
+/// The Resource describing an ongoing play-test
+#[derive(Resource, Default)]
+pub struct Playtest {
+    spawn_location: Option<Transform>,
+    camera_transform: Option<Transform>,
+    camera_zoom: Option<f32>,
+}
+
+impl Playtest {
+    /// Returns where the player should spawn, which is the level's spawn point outside of play-tests
+    pub fn spawn_location(&self, ls: &LevelSettings) -> Transform {
+        self.spawn_location.unwrap_or(ls.spawn_location)
+    }
+}
+
+/// This System starts a play-test at the hovered grid cell
+fn play_from_here(
+    input: Res<PlayerInput>,
+    hovering_block: Res<HoveringBlock>,
+    ls: Res<LevelSettings>,
+    camera: Query<(&Transform, &TimeEase), With<CameraMarker>>,
+    mut playtest: ResMut<Playtest>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if !input.play_from_here_pressed() {
+        return;
+    }
+    let (x, y) = hovering_block.hovering;
+    playtest.spawn_location = Some(Transform::from_xyz(
+        x as f32 * 16. + 8.,
+        y as f32 * 16. + 8.,
+        ls.spawn_location.translation.z,
+    ));
+    let (camera_transform, time_ease) = camera.single();
+    playtest.camera_transform = Some(*camera_transform);
+    playtest.camera_zoom = Some(time_ease.get_end_val());
+    next_state.set(AppState::Playing);
+}
+
+/// This System moves the camera back to where it was before the play-test started
+fn end_playtest(
+    camera_settings: Res<CameraSettings>,
+    mut playtest: ResMut<Playtest>,
+    mut camera: Query<(&mut Transform, &mut TimeEase), With<CameraMarker>>,
+) {
+    let (mut transform, mut time_ease) = camera.single_mut();
+    if let Some(camera_transform) = playtest.camera_transform.take() {
+        *transform = camera_transform;
+    }
+    if let Some(camera_zoom) = playtest.camera_zoom.take() {
+        time_ease.set_ease(
+            camera_zoom,
+            EasingFunction::Sine,
+            EasingType::InOut,
+            camera_settings.zoom_duration,
+        );
+    }
+    playtest.spawn_location = None;
+}
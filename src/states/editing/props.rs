@@ -0,0 +1,260 @@
+use bevy::prelude::*;
+use std::{fs::read_dir, path::Path};
+
+use super::{
+    cursor_not_over_ui, markers::not_dragging, place_block, tool_is, EditorTool, HoveringBlock,
+};
+use crate::{
+    config::PropSettings,
+    input::PlayerInput,
+    level_management::{
+        execute_level_queues,
+        props::{Prop, PropMarker},
+        saving::SaveState,
+        Level,
+    },
+    states::AppState,
+};
+
+/// The Plugin that allows placing, selecting, moving and deleting props in the editor
+pub struct PropEditorPlugin;
+
+impl Plugin for PropEditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PropEditor>()
+            .add_systems(Startup, load_prop_catalogue)
+            .add_systems(OnExit(AppState::Editing), release_prop)
+            .add_systems(
+                Update,
+                (
+                    grab_prop.run_if(cursor_not_over_ui).run_if(not_dragging),
+                    drag_prop,
+                    edit_selected_prop,
+                    cycle_prop_image,
+                    draw_prop_selection,
+                )
+                    .chain()
+                    .after(place_block)
+                    .before(execute_level_queues)
+                    .run_if(tool_is(EditorTool::Props))
+                    .run_if(in_state(AppState::Editing)),
+            );
+    }
+}
+
+/// The smallest and largest scale a prop can have
+const SCALE_RANGE: (f32, f32) = (0.1, 10.);
+
+/// The Resource keeping track of the props tool
+#[derive(Resource, Default)]
+pub struct PropEditor {
+    /// The images that can be placed, relative to the assets folder
+    pub catalogue: Vec<String>,
+    /// The index of the image in the catalogue that is placed by the next click
+    pub current: usize,
+    /// The id of the selected prop
+    pub selected: Option<u32>,
+    /// The offset from the cursor to the prop being dragged
+    grab_offset: Option<Vec2>,
+}
+
+impl PropEditor {
+    /// Returns the image that is placed by the next click
+    pub fn current_image(&self) -> Option<&String> {
+        self.catalogue.get(self.current)
+    }
+}
+
+/// Helper function that returns the size of a prop in world units, falling back to a block while its image is loading
+fn prop_size(
+    level: &Level,
+    id: u32,
+    textures: &Query<&Handle<Image>, With<PropMarker>>,
+    images: &Assets<Image>,
+) -> Vec2 {
+    let scale = level.props.get(id).map_or(1., |prop| prop.scale);
+    level
+        .props
+        .entity_map
+        .get(&id)
+        .and_then(|&entity| textures.get(entity).ok())
+        .and_then(|texture| images.get(texture))
+        .map_or(Vec2::splat(16.), Image::size_f32)
+        * scale
+}
+
+/// Helper function that returns the topmost prop under the given point
+fn prop_at(
+    level: &Level,
+    point: Vec2,
+    textures: &Query<&Handle<Image>, With<PropMarker>>,
+    images: &Assets<Image>,
+) -> Option<u32> {
+    level
+        .props
+        .props
+        .iter()
+        .filter(|(&id, prop)| {
+            Rect::from_center_size(prop.position(), prop_size(level, id, textures, images))
+                .contains(point)
+        })
+        .max_by(|(_, a), (_, b)| a.z.total_cmp(&b.z))
+        .map(|(&id, _)| id)
+}
+
+/// This System collects the images in the prop directory
+fn load_prop_catalogue(prop_settings: Res<PropSettings>, mut prop_editor: ResMut<PropEditor>) {
+    let Ok(entries) = read_dir(&prop_settings.directory) else {
+        return;
+    };
+    let mut catalogue: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "png"))
+        .filter_map(|path| {
+            path.strip_prefix(Path::new("assets"))
+                .ok()
+                .map(|path| path.to_string_lossy().replace('\\', "/"))
+        })
+        .collect();
+    catalogue.sort();
+    prop_editor.catalogue = catalogue;
+}
+
+/// This System selects the clicked prop, or places a new one if no prop was clicked
+fn grab_prop(
+    input: Res<PlayerInput>,
+    hovering_block: Res<HoveringBlock>,
+    mut level: ResMut<Level>,
+    mut prop_editor: ResMut<PropEditor>,
+    mut save_state: ResMut<SaveState>,
+    textures: Query<&Handle<Image>, With<PropMarker>>,
+    images: Res<Assets<Image>>,
+) {
+    if !input.left_clicked() {
+        return;
+    }
+    let cursor = hovering_block.cursor;
+    if let Some(id) = prop_at(&level, cursor, &textures, &images) {
+        prop_editor.selected = Some(id);
+        prop_editor.grab_offset = Some(level.props.get(id).unwrap().position() - cursor);
+    } else if let Some(image) = prop_editor.current_image().cloned() {
+        let id = level.props.insert(Prop::new(image, cursor));
+        prop_editor.selected = Some(id);
+        prop_editor.grab_offset = Some(Vec2::ZERO);
+        save_state.mark_dirty();
+    } else {
+        prop_editor.selected = None;
+    }
+}
+
+/// This System moves the grabbed prop along with the cursor and releases it once the mouse button is let go
+fn drag_prop(
+    input: Res<PlayerInput>,
+    hovering_block: Res<HoveringBlock>,
+    mut level: ResMut<Level>,
+    mut prop_editor: ResMut<PropEditor>,
+    mut save_state: ResMut<SaveState>,
+) {
+    let (Some(offset), Some(id)) = (prop_editor.grab_offset, prop_editor.selected) else {
+        return;
+    };
+    if !input.left_click_held() {
+        prop_editor.grab_offset = None;
+        return;
+    }
+    let Some(mut prop) = level.props.get(id).cloned() else {
+        return;
+    };
+    let position = hovering_block.cursor + offset;
+    if prop.position() != position {
+        prop.position = (position.x, position.y);
+        level.props.update(id, prop);
+        save_state.mark_dirty();
+    }
+}
+
+/// This System deletes, flips, scales or reorders the selected prop
+fn edit_selected_prop(
+    input: Res<PlayerInput>,
+    prop_settings: Res<PropSettings>,
+    mut level: ResMut<Level>,
+    mut prop_editor: ResMut<PropEditor>,
+    mut save_state: ResMut<SaveState>,
+) {
+    let Some(id) = prop_editor.selected else {
+        return;
+    };
+    let Some(mut prop) = level.props.get(id).cloned() else {
+        prop_editor.selected = None;
+        return;
+    };
+    if input.delete_pressed() {
+        level.props.remove(id);
+        prop_editor.selected = None;
+        prop_editor.grab_offset = None;
+        save_state.mark_dirty();
+        return;
+    }
+    if input.flip_pressed() {
+        prop.flip_x = !prop.flip_x;
+    }
+    if input.scale_up_pressed() {
+        prop.scale *= prop_settings.scale_step;
+    }
+    if input.scale_down_pressed() {
+        prop.scale /= prop_settings.scale_step;
+    }
+    prop.scale = prop.scale.clamp(SCALE_RANGE.0, SCALE_RANGE.1);
+    if input.raise_pressed() {
+        prop.z += 1.;
+    }
+    if input.lower_pressed() {
+        prop.z -= 1.;
+    }
+    if level.props.get(id) != Some(&prop) {
+        level.props.update(id, prop);
+        save_state.mark_dirty();
+    }
+}
+
+/// This System changes which image is placed by the next click
+fn cycle_prop_image(input: Res<PlayerInput>, mut prop_editor: ResMut<PropEditor>) {
+    let count = prop_editor.catalogue.len();
+    if count == 0 {
+        return;
+    }
+    if input.next_prop_pressed() {
+        prop_editor.current = (prop_editor.current + 1) % count;
+    }
+    if input.previous_prop_pressed() {
+        prop_editor.current = (prop_editor.current + count - 1) % count;
+    }
+}
+
+/// This System outlines the selected prop
+fn draw_prop_selection(
+    level: Res<Level>,
+    prop_editor: Res<PropEditor>,
+    textures: Query<&Handle<Image>, With<PropMarker>>,
+    images: Res<Assets<Image>>,
+    mut gizmos: Gizmos,
+) {
+    let Some(id) = prop_editor.selected else {
+        return;
+    };
+    let Some(prop) = level.props.get(id) else {
+        return;
+    };
+    gizmos.rect_2d(
+        prop.position(),
+        0.,
+        prop_size(&level, id, &textures, &images) + Vec2::splat(2.),
+        Color::srgb(1., 0.85, 0.3),
+    );
+}
+
+/// This System lets go of the grabbed prop when leaving [AppState::Editing]
+fn release_prop(mut prop_editor: ResMut<PropEditor>) {
+    prop_editor.grab_offset = None;
+}
@@ -0,0 +1,54 @@
+use bevy::prelude::*;
+
+use super::{
+    edit_without_preview, history::EditHistory, hud::EditorMessage, move_block_to_cursor,
+    selection::Selection, HoveringBlock,
+};
+use crate::{
+    input::PlayerInput,
+    level_management::{execute_level_queues, saving::SaveState, Level},
+    states::AppState,
+};
+
+/// The Plugin containing the editors find-and-replace command for materials
+pub struct ReplacePlugin;
+
+impl Plugin for ReplacePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            replace_hovered_material
+                .after(move_block_to_cursor)
+                .before(execute_level_queues)
+                .run_if(in_state(AppState::Editing)),
+        );
+    }
+}
+
+/// This System replaces every block with the hovered blocks material by the selected material, in all layers.
+/// If a region is selected, only blocks inside of it are replaced
+fn replace_hovered_material(
+    input: Res<PlayerInput>,
+    selection: Res<Selection>,
+    mut hovering_block: ResMut<HoveringBlock>,
+    mut level: ResMut<Level>,
+    mut history: ResMut<EditHistory>,
+    mut save_state: ResMut<SaveState>,
+    mut message: ResMut<EditorMessage>,
+) {
+    if !input.replace_pressed() {
+        return;
+    }
+    let Some(from) = hovering_block.original_material else {
+        return;
+    };
+    let to = hovering_block.selected_material;
+    let changes = edit_without_preview(&mut hovering_block, &mut level, |level| {
+        level.replace_material(from, to, selection.region)
+    });
+    if !changes.is_empty() {
+        message.show(format!("Replaced {} blocks of {:?}", changes.len(), from));
+        save_state.mark_dirty();
+        history.record(changes);
+    }
+}
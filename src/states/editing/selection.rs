@@ -0,0 +1,89 @@
+use bevy::prelude::*;
+
+use super::{cursor_not_over_ui, move_block_to_cursor, HoveringBlock};
+use crate::{input::PlayerInput, states::AppState};
+
+/// The Plugin that allows selecting a rectangular region of the grid
+pub struct SelectionPlugin;
+
+impl Plugin for SelectionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Selection>().add_systems(
+            Update,
+            (
+                start_selection.run_if(cursor_not_over_ui),
+                extend_selection,
+                clear_selection,
+                draw_selection,
+            )
+                .chain()
+                .after(move_block_to_cursor)
+                .run_if(in_state(AppState::Editing)),
+        );
+    }
+}
+
+/// The Resource holding the selected region of grid cells
+#[derive(Resource, Default)]
+pub struct Selection {
+    /// The selected cells. Both corners are part of the selection
+    pub region: Option<IRect>,
+    /// The cell the selection was started at, while it is being dragged open
+    anchor: Option<IVec2>,
+}
+
+/// Helper function that converts a grid cell to a vector
+fn cell(position: (i32, i32)) -> IVec2 {
+    IVec2::new(position.0, position.1)
+}
+
+/// This System starts a new selection at the hovered cell when the right mouse button is clicked
+fn start_selection(
+    input: Res<PlayerInput>,
+    hovering_block: Res<HoveringBlock>,
+    mut selection: ResMut<Selection>,
+) {
+    if !input.right_clicked() {
+        return;
+    }
+    let anchor = cell(hovering_block.hovering);
+    selection.anchor = Some(anchor);
+    selection.region = Some(IRect::from_corners(anchor, anchor));
+}
+
+/// This System drags the selection open while the right mouse button is held
+fn extend_selection(
+    input: Res<PlayerInput>,
+    hovering_block: Res<HoveringBlock>,
+    mut selection: ResMut<Selection>,
+) {
+    let Some(anchor) = selection.anchor else {
+        return;
+    };
+    if !input.right_click_held() {
+        selection.anchor = None;
+        return;
+    }
+    let region = IRect::from_corners(anchor, cell(hovering_block.hovering));
+    if selection.region != Some(region) {
+        selection.region = Some(region);
+    }
+}
+
+/// This System removes the selection when the cancel keybind is pressed
+fn clear_selection(input: Res<PlayerInput>, mut selection: ResMut<Selection>) {
+    if input.cancel_pressed() {
+        selection.region = None;
+        selection.anchor = None;
+    }
+}
+
+/// This System outlines the selected region
+fn draw_selection(selection: Res<Selection>, mut gizmos: Gizmos) {
+    let Some(region) = selection.region else {
+        return;
+    };
+    let min = region.min.as_vec2() * 16.;
+    let max = (region.max + IVec2::ONE).as_vec2() * 16.;
+    gizmos.rect_2d((min + max) / 2., 0., max - min, Color::srgb(0.3, 0.7, 1.));
+}
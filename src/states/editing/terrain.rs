@@ -0,0 +1,308 @@
+use bevy::prelude::*;
+
+use super::{
+    cursor_not_over_ui, edit_without_preview, history::EditHistory, hud::EditorMessage,
+    markers::not_dragging, move_block_to_cursor, selection::Selection, tool_is, EditorTool,
+    HoveringBlock,
+};
+use crate::{
+    config::{LevelSettings, TerrainSettings},
+    input::PlayerInput,
+    level_management::{
+        execute_level_queues, saving::SaveState, BlockChange, Layer, Level, LevelLayer,
+    },
+    states::AppState,
+    utilities::assets::Material,
+};
+
+/// The Plugin containing the editors terrain tool, which sculpts hills and carves caves
+pub struct TerrainPlugin;
+
+impl Plugin for TerrainPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TerrainStroke>().add_systems(
+            Update,
+            (
+                sculpt_terrain
+                    .run_if(cursor_not_over_ui)
+                    .run_if(not_dragging),
+                finish_stroke,
+                carve_caves,
+                draw_terrain_cursor,
+            )
+                .chain()
+                .after(move_block_to_cursor)
+                .before(execute_level_queues)
+                .run_if(tool_is(EditorTool::Terrain))
+                .run_if(in_state(AppState::Editing)),
+        );
+    }
+}
+
+/// The Resource collecting the changes of the current sculpting stroke, so it can be undone in one step
+#[derive(Resource, Default)]
+struct TerrainStroke {
+    changes: Vec<BlockChange>,
+    last_cell: Option<(i32, i32)>,
+}
+
+/// The materials terrain is built from
+struct TerrainMaterials {
+    grass: Material,
+    fill: Material,
+}
+
+impl TerrainMaterials {
+    /// Picks the selected grass material, or the default one if the selected material has no grass on top
+    fn new(hovering_block: &HoveringBlock, terrain_settings: &TerrainSettings) -> Self {
+        let grass = hovering_block
+            .selected_material
+            .filter(Material::is_grass)
+            .unwrap_or(terrain_settings.default_grass);
+        TerrainMaterials {
+            grass,
+            fill: terrain_settings.fill_material.unwrap_or(grass),
+        }
+    }
+}
+
+/// Helper function that changes a block and remembers the change
+fn set(
+    level: &mut LevelLayer,
+    layer: Layer,
+    position: (i32, i32),
+    material: Option<Material>,
+    changes: &mut Vec<BlockChange>,
+) {
+    let before = level.material_map.get(&position).copied();
+    if before == material {
+        return;
+    }
+    level.set_block(position, material);
+    changes.push(BlockChange {
+        layer,
+        position,
+        before,
+        after: material,
+    });
+}
+
+/// Helper function that moves the surface at or below the given cell to the cell's height.
+/// A solid cell lowers the run of blocks it is part of, an empty cell raises the first run of blocks below it,
+/// so platforms and overhangs elsewhere in the column are left alone. Without blocks below, the column is filled from the given floor upwards
+fn sculpt_column(
+    level: &mut LevelLayer,
+    layer: Layer,
+    (x, height): (i32, i32),
+    floor: i32,
+    materials: &TerrainMaterials,
+) -> Vec<BlockChange> {
+    let mut changes = Vec::new();
+    let solid = |level: &LevelLayer, y: i32| level.material_map.contains_key(&(x, y));
+    let top = if solid(level, height) {
+        (height..).take_while(|y| solid(level, *y)).last()
+    } else {
+        (floor + 1..height).rev().find(|y| solid(level, *y))
+    };
+    let bottom = match top {
+        Some(top) if top == height => return changes,
+        Some(top) if top > height => {
+            (height + 1..=top).for_each(|y| set(level, layer, (x, y), None, &mut changes));
+            set(
+                level,
+                layer,
+                (x, height),
+                Some(materials.grass),
+                &mut changes,
+            );
+            return changes;
+        }
+        Some(top) => {
+            if level
+                .material_map
+                .get(&(x, top))
+                .is_some_and(Material::is_grass)
+            {
+                set(level, layer, (x, top), Some(materials.fill), &mut changes);
+            }
+            top + 1
+        }
+        None if height <= floor => return changes,
+        None => floor + 1,
+    };
+    (bottom..height).for_each(|y| set(level, layer, (x, y), Some(materials.fill), &mut changes));
+    set(
+        level,
+        layer,
+        (x, height),
+        Some(materials.grass),
+        &mut changes,
+    );
+    changes
+}
+
+/// Helper function that returns a pseudo random value between 0 and 1 for a lattice point
+fn hash(x: i32, y: i32, seed: u32) -> f32 {
+    let mut h = (x as u32).wrapping_mul(374_761_393)
+        ^ (y as u32).wrapping_mul(668_265_263)
+        ^ seed.wrapping_mul(2_246_822_519);
+    h = (h ^ (h >> 13)).wrapping_mul(1_274_126_177);
+    h ^= h >> 16;
+    h as f32 / u32::MAX as f32
+}
+
+/// Helper function for smoothly interpolated value noise between 0 and 1
+fn value_noise(point: Vec2, seed: u32) -> f32 {
+    let cell = point.floor();
+    let t = point - cell;
+    let t = t * t * (Vec2::splat(3.) - 2. * t);
+    let (x, y) = (cell.x as i32, cell.y as i32);
+    let bottom = hash(x, y, seed) + (hash(x + 1, y, seed) - hash(x, y, seed)) * t.x;
+    let top = hash(x, y + 1, seed) + (hash(x + 1, y + 1, seed) - hash(x, y + 1, seed)) * t.x;
+    bottom + (top - bottom) * t.y
+}
+
+/// Helper function that returns whether the given cell is part of a cave
+fn is_cave((x, y): (i32, i32), terrain_settings: &TerrainSettings, seed: u32) -> bool {
+    let point = Vec2::new(x as f32, y as f32) / terrain_settings.cave_scale.max(1.);
+    let noise =
+        (value_noise(point, seed) * 2. + value_noise(point * 2., seed.wrapping_add(1))) / 3.;
+    noise > terrain_settings.cave_threshold
+}
+
+/// Helper function that returns the cells between the previous cell of a stroke and the current one, one for each column, with interpolated heights.
+/// The previous cell is left out, as it was already sculpted
+fn stroke_cells(previous: Option<(i32, i32)>, current: (i32, i32)) -> Vec<(i32, i32)> {
+    let Some(previous) = previous.filter(|previous| previous.0 != current.0) else {
+        return vec![current];
+    };
+    let direction = (current.0 - previous.0).signum();
+    let columns = (current.0 - previous.0).abs();
+    (1..=columns)
+        .map(|step| {
+            let t = step as f32 / columns as f32;
+            let height = previous.1 as f32 + (current.1 - previous.1) as f32 * t;
+            (previous.0 + step * direction, height.round() as i32)
+        })
+        .collect()
+}
+
+/// This System moves the ground surface below the hovered cell to it while the mouse button is held
+///
+/// A fast drag also sculpts every column the cursor skipped since the last frame
+fn sculpt_terrain(
+    input: Res<PlayerInput>,
+    terrain_settings: Res<TerrainSettings>,
+    ls: Res<LevelSettings>,
+    mut stroke: ResMut<TerrainStroke>,
+    mut hovering_block: ResMut<HoveringBlock>,
+    mut level: ResMut<Level>,
+    mut save_state: ResMut<SaveState>,
+) {
+    if !input.left_click_held() {
+        return;
+    }
+    let cell = hovering_block.hovering;
+    if stroke.last_cell == Some(cell) {
+        return;
+    }
+    let cells = stroke_cells(stroke.last_cell, cell);
+    stroke.last_cell = Some(cell);
+    let materials = TerrainMaterials::new(&hovering_block, &terrain_settings);
+    let floor = (ls.death_height / 16.).floor() as i32;
+    let layer = hovering_block.layer;
+    let changes = edit_without_preview(&mut hovering_block, &mut level, |level| {
+        let level = level.layer_mut(layer);
+        cells
+            .into_iter()
+            .flat_map(|cell| sculpt_column(level, layer, cell, floor, &materials))
+            .collect::<Vec<BlockChange>>()
+    });
+    if !changes.is_empty() {
+        save_state.mark_dirty();
+        stroke.changes.extend(changes);
+    }
+}
+
+/// This System records the finished sculpting stroke, once the mouse button is let go
+fn finish_stroke(
+    input: Res<PlayerInput>,
+    mut stroke: ResMut<TerrainStroke>,
+    mut history: ResMut<EditHistory>,
+) {
+    if input.left_click_held() || stroke.last_cell.is_none() {
+        return;
+    }
+    stroke.last_cell = None;
+    history.record(std::mem::take(&mut stroke.changes));
+}
+
+/// This System fills the selected region with terrain and carves caves into it using noise
+#[allow(clippy::too_many_arguments)]
+fn carve_caves(
+    input: Res<PlayerInput>,
+    terrain_settings: Res<TerrainSettings>,
+    selection: Res<Selection>,
+    mut carved: Local<u32>,
+    mut hovering_block: ResMut<HoveringBlock>,
+    mut level: ResMut<Level>,
+    mut history: ResMut<EditHistory>,
+    mut save_state: ResMut<SaveState>,
+    mut message: ResMut<EditorMessage>,
+) {
+    if !input.carve_pressed() {
+        return;
+    }
+    let Some(region) = selection.region else {
+        message.show("Select a region with the right mouse button to carve caves in");
+        return;
+    };
+    // Every carve uses another seed, so carving again gives different caves
+    let seed = terrain_settings.cave_seed.wrapping_add(*carved);
+    *carved += 1;
+    let materials = TerrainMaterials::new(&hovering_block, &terrain_settings);
+    let layer = hovering_block.layer;
+    let changes = edit_without_preview(&mut hovering_block, &mut level, |level| {
+        let level = level.layer_mut(layer);
+        let solid = |position: (i32, i32)| {
+            if region.contains(IVec2::new(position.0, position.1)) {
+                !is_cave(position, &terrain_settings, seed)
+            } else {
+                level.material_map.contains_key(&position)
+            }
+        };
+        let cells: Vec<((i32, i32), Option<Material>)> = (region.min.x..=region.max.x)
+            .flat_map(|x| (region.min.y..=region.max.y).map(move |y| (x, y)))
+            .map(|(x, y)| {
+                let material = if !solid((x, y)) {
+                    None
+                } else if solid((x, y + 1)) {
+                    Some(materials.fill)
+                } else {
+                    Some(materials.grass)
+                };
+                ((x, y), material)
+            })
+            .collect();
+        let mut changes = Vec::new();
+        cells.into_iter().for_each(|(position, material)| {
+            set(level, layer, position, material, &mut changes);
+        });
+        changes
+    });
+    if !changes.is_empty() {
+        save_state.mark_dirty();
+        history.record(changes);
+    }
+}
+
+/// This System outlines the cell the ground surface is moved to
+fn draw_terrain_cursor(hovering_block: Res<HoveringBlock>, mut gizmos: Gizmos) {
+    let (x, y) = hovering_block.hovering;
+    gizmos.rect_2d(
+        Vec2::new(x as f32 * 16. + 8., y as f32 * 16. + 8.),
+        0.,
+        Vec2::splat(16.),
+        Color::srgb(0.5, 1., 0.4),
+    );
+}
@@ -1,16 +1,18 @@
 use bevy::prelude::*;
-use bevy_rapier2d::prelude::{Collider, LockedAxes, RigidBody};
+use bevy_rapier2d::prelude::{LockedAxes, RigidBody};
 use bevy_tnua::prelude::TnuaControllerBundle;
-use bevy_tnua_rapier2d::{TnuaRapier2dIOBundle, TnuaRapier2dSensorShape};
+use bevy_tnua_rapier2d::TnuaRapier2dIOBundle;
 
 use crate::{
     camera::{
-        movement::follow::{FollowMarker, Target},
+        movement::follow::{FollowMarker, Target, TargetMarker},
         CameraMarker,
     },
-    config::{LevelSettings, PlayerSettings},
-    player::{PlayerBundle, PlayerMarker},
-    utilities::assets::{PlayerAnimationMap, PlayerAnimationState},
+    characters::{Character, CharacterRegistry, SelectedCharacter},
+    config::{CharacterControllerSettings, LevelSettings, PlayerSettings},
+    input::PlayerInput,
+    player::{PlayerBundle, PlayerMarker, PlayerNumber},
+    states::editing::playtest::Playtest,
 };
 
 use super::AppState;
@@ -20,67 +22,145 @@ pub struct PlayingPlugin;
 
 impl Plugin for PlayingPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnExit(AppState::Playing), exit_playing)
-            .add_systems(OnEnter(AppState::Playing), enter_playing);
+        app.init_resource::<Coop>()
+            .add_systems(OnExit(AppState::Playing), exit_playing)
+            .add_systems(OnEnter(AppState::Playing), enter_playing)
+            .add_systems(Update, toggle_coop.run_if(in_state(AppState::Playing)));
     }
 }
 
+/// The Resource that decides whether a second player plays along on the same screen
+#[derive(Resource, Default)]
+pub struct Coop(pub bool);
+
+impl Coop {
+    /// Returns how many players are playing
+    pub fn player_count(&self) -> usize {
+        if self.0 {
+            2
+        } else {
+            1
+        }
+    }
+}
+
+/// Helper function that spawns the player with the given number as the given character
+fn spawn_player(
+    commands: &mut Commands,
+    number: PlayerNumber,
+    transform: Transform,
+    character: &Character,
+    ps: &PlayerSettings,
+    ccs: &CharacterControllerSettings,
+) {
+    // Name doesn't implement copy, so it can't be taken from the bundle in the settings
+    let name = match number.0 {
+        0 => ps.player_bundle.name.clone(),
+        _ => Name::new(format!(
+            "{} {}",
+            ps.player_bundle.name.as_str(),
+            number.0 + 1
+        )),
+    };
+    commands.spawn((
+        SpriteBundle {
+            transform,
+            sprite: Sprite {
+                color: number.color(character.color(), ps),
+                ..default()
+            },
+            ..default()
+        },
+        PlayerBundle {
+            number,
+            target_marker: TargetMarker::new(Target::Player(number.0)),
+            name,
+            ..ps.player_bundle
+        },
+        character.components(ps, ccs),
+        RigidBody::Dynamic,
+        TnuaRapier2dIOBundle::default(),
+        TnuaControllerBundle::default(),
+        LockedAxes::ROTATION_LOCKED,
+    ));
+}
+
 /// This System is run whenever the [AppState] changes from [AppState::Playing] and cleans up everything unnescessary
 fn exit_playing(
     mut commands: Commands,
     cameras: Query<Entity, With<CameraMarker>>,
-    player: Query<Entity, With<PlayerMarker>>,
+    players: Query<Entity, With<PlayerMarker>>,
 ) {
     cameras.iter().for_each(|entity| {
         commands.entity(entity).remove::<FollowMarker>();
     });
-    commands.entity(player.single()).despawn();
+    players.iter().for_each(|entity| {
+        commands.entity(entity).despawn();
+    });
 }
 
 /// This System is run whenever the [AppState] changes to [AppState::Playing] and prepares everything required
+#[allow(clippy::too_many_arguments)]
 fn enter_playing(
     mut commands: Commands,
     cameras: Query<Entity, With<CameraMarker>>,
     ls: Res<LevelSettings>,
     ps: Res<PlayerSettings>,
-    player_animation_map: Res<PlayerAnimationMap>,
+    ccs: Res<CharacterControllerSettings>,
+    playtest: Res<Playtest>,
+    registry: Res<CharacterRegistry>,
+    selected: Res<SelectedCharacter>,
+    coop: Res<Coop>,
 ) {
-    let texture = player_animation_map
-        .0
-        .get(&PlayerAnimationState::IDLE)
-        .unwrap()
-        .texture
-        .clone();
-    commands.spawn((
-        SpriteBundle {
-            texture,
-            transform: ls.spawn_location,
-            ..default()
-        },
-        PlayerBundle {
-            // Hack because Name doesn't implement clone and copy
-            name: ps.player_bundle.name.clone(),
-            ..ps.player_bundle
-        },
-        TextureAtlas {
-            layout: player_animation_map
-                .0
-                .get(&PlayerAnimationState::IDLE)
-                .unwrap()
-                .texture_atlas_layout
-                .clone(),
-            index: 1,
-        },
-        RigidBody::Dynamic,
-        TnuaRapier2dIOBundle::default(),
-        TnuaControllerBundle::default(),
-        LockedAxes::ROTATION_LOCKED,
-        TnuaRapier2dSensorShape(Collider::capsule(Vec2::new(0., -ps.sensor_collider_size.y / 2. - 2.), Vec2::new(0., ps.sensor_collider_size.y / 2. - 2.), ps.sensor_collider_size.x / 2.)),
-        Collider::capsule(Vec2::new(0., -ps.collider_size.y / 2. - 2.), Vec2::new(0., ps.collider_size.y / 2. - 2.), ps.collider_size.x / 2.),
-    ));
+    let character = registry.get(&selected.0);
+    let spawn = playtest.spawn_location(&ls);
+    (0..coop.player_count())
+        .map(PlayerNumber)
+        .for_each(|number| {
+            let transform = number.spawn_location(spawn, &ps);
+            spawn_player(&mut commands, number, transform, &character, &ps, &ccs);
+        });
     cameras.iter().for_each(|entity| {
         commands
             .entity(entity)
-            .insert(FollowMarker::new(Target::Player));
+            .insert(FollowMarker::all(Target::players()));
     })
 }
+
+/// This System lets a second player join next to the first one, or leave again
+#[allow(clippy::too_many_arguments)]
+fn toggle_coop(
+    mut commands: Commands,
+    input: Res<PlayerInput>,
+    ls: Res<LevelSettings>,
+    ps: Res<PlayerSettings>,
+    ccs: Res<CharacterControllerSettings>,
+    playtest: Res<Playtest>,
+    registry: Res<CharacterRegistry>,
+    selected: Res<SelectedCharacter>,
+    mut coop: ResMut<Coop>,
+    players: Query<(Entity, &PlayerNumber, &Transform)>,
+) {
+    if !input.toggle_coop_pressed() {
+        return;
+    }
+    coop.0 = !coop.0;
+    if !coop.0 {
+        players
+            .iter()
+            .filter(|(_, number, _)| number.0 >= coop.player_count())
+            .for_each(|(entity, ..)| {
+                commands.entity(entity).despawn();
+            });
+        return;
+    }
+    // The new player joins beside the first one, instead of wherever the level starts
+    let spawn = players
+        .iter()
+        .find(|(_, number, _)| number.0 == 0)
+        .map_or(playtest.spawn_location(&ls), |(.., transform)| *transform);
+    let character = registry.get(&selected.0);
+    let number = PlayerNumber(1);
+    let transform = number.spawn_location(spawn, &ps);
+    spawn_player(&mut commands, number, transform, &character, &ps, &ccs);
+}
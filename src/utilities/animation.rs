@@ -0,0 +1,202 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+use std::{collections::HashMap, fs::read_to_string};
+
+use crate::config::AnimationSettings;
+
+/// The Plugin that plays [SpriteAnimation]s on any entity
+pub struct SpriteAnimationPlugin;
+
+impl Plugin for SpriteAnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AnimationClips>()
+            .add_systems(Startup, load_animation_clips)
+            .add_systems(Update, (switch_clips, animate_sprites).chain());
+    }
+}
+
+/// How a clip is described in the animation file
+#[derive(Deserialize)]
+struct ClipDefinition {
+    /// The path of the sprite sheet, relative to the assets folder
+    texture: String,
+    /// The size of a single frame in pixels. The frames are laid out in a single row
+    frame_size: (u32, u32),
+    frames: usize,
+    fps: f32,
+    /// Whether the clip starts over once done, or stops on its last frame
+    #[serde(default = "default_looping")]
+    looping: bool,
+}
+
+/// Helper function for serde, so clips loop unless told otherwise
+fn default_looping() -> bool {
+    true
+}
+
+/// A loaded animation clip, ready to be played
+#[derive(Clone)]
+pub struct AnimationClip {
+    pub texture: Handle<Image>,
+    pub layout: Handle<TextureAtlasLayout>,
+    /// The size of a single frame in pixels
+    pub frame_size: Vec2,
+    pub frames: usize,
+    pub fps: f32,
+    pub looping: bool,
+}
+
+/// The Resource holding every animation clip, grouped into named sets like `player`
+#[derive(Resource, Default)]
+pub struct AnimationClips(pub HashMap<String, HashMap<String, AnimationClip>>);
+
+impl AnimationClips {
+    /// Returns the clip with the given name from the given set
+    pub fn get(&self, set: &str, clip: &str) -> Option<&AnimationClip> {
+        self.0.get(set).and_then(|clips| clips.get(clip))
+    }
+}
+
+/// The Component that plays a clip from [AnimationClips] on the sprite of its entity
+#[derive(Component, Clone)]
+pub struct SpriteAnimation {
+    /// The set of clips the entity plays from
+    pub set: String,
+    clip: String,
+    frame: usize,
+    elapsed: f32,
+}
+
+impl SpriteAnimation {
+    /// Creates an animation playing the given clip from the given set
+    pub fn new(set: impl Into<String>, clip: impl Into<String>) -> Self {
+        SpriteAnimation {
+            set: set.into(),
+            clip: clip.into(),
+            frame: 0,
+            elapsed: 0.,
+        }
+    }
+
+    /// Returns the name of the clip being played
+    pub fn clip(&self) -> &str {
+        &self.clip
+    }
+
+    /// Switches to the given clip and starts it from its first frame, unless it is already playing
+    pub fn play(&mut self, clip: &str) {
+        if self.clip != clip {
+            self.clip = clip.to_string();
+            self.frame = 0;
+            self.elapsed = 0.;
+        }
+    }
+}
+
+/// This System reads the animation file and loads the sprite sheets of every clip in it
+fn load_animation_clips(
+    settings: Res<AnimationSettings>,
+    asset_server: Res<AssetServer>,
+    mut layouts: ResMut<Assets<TextureAtlasLayout>>,
+    mut animation_clips: ResMut<AnimationClips>,
+) {
+    let definitions: HashMap<String, HashMap<String, ClipDefinition>> =
+        match read_to_string(&settings.clip_path)
+            .map_err(|e| e.to_string())
+            .and_then(|json| serde_json::from_str(&json).map_err(|e| e.to_string()))
+        {
+            Ok(definitions) => definitions,
+            Err(e) => {
+                eprintln!("Failed to load animations: {}", e);
+                return;
+            }
+        };
+    animation_clips.0 = definitions
+        .into_iter()
+        .map(|(set, clips)| {
+            let clips = clips
+                .into_iter()
+                .filter_map(|(name, definition)| {
+                    // A frame time of zero or less would never be used up, so advancing the clip would never end
+                    if definition.fps <= 0. || !definition.fps.is_finite() {
+                        eprintln!(
+                            "Skipped animation {}/{}: fps must be positive and finite, but is {}",
+                            set, name, definition.fps
+                        );
+                        return None;
+                    }
+                    let layout = TextureAtlasLayout::from_grid(
+                        UVec2::new(definition.frame_size.0, definition.frame_size.1),
+                        definition.frames as u32,
+                        1,
+                        None,
+                        None,
+                    );
+                    let clip = AnimationClip {
+                        texture: asset_server.load(definition.texture),
+                        layout: layouts.add(layout),
+                        frame_size: Vec2::new(
+                            definition.frame_size.0 as f32,
+                            definition.frame_size.1 as f32,
+                        ),
+                        frames: definition.frames.max(1),
+                        fps: definition.fps,
+                        looping: definition.looping,
+                    };
+                    Some((name, clip))
+                })
+                .collect();
+            (set, clips)
+        })
+        .collect();
+}
+
+/// This System swaps in the sprite sheet of a [SpriteAnimation] whenever its clip changes
+fn switch_clips(
+    mut commands: Commands,
+    animation_clips: Res<AnimationClips>,
+    mut query: Query<(Entity, &SpriteAnimation, &mut Handle<Image>), Changed<SpriteAnimation>>,
+) {
+    query
+        .iter_mut()
+        .for_each(|(entity, animation, mut texture)| {
+            let Some(clip) = animation_clips.get(&animation.set, &animation.clip) else {
+                return;
+            };
+            if *texture != clip.texture {
+                *texture = clip.texture.clone();
+            }
+            commands.entity(entity).insert(TextureAtlas {
+                layout: clip.layout.clone(),
+                index: animation.frame,
+            });
+        });
+}
+
+/// This System advances every [SpriteAnimation] according to the fps of its clip
+fn animate_sprites(
+    time: Res<Time>,
+    animation_clips: Res<AnimationClips>,
+    mut query: Query<(&mut SpriteAnimation, &mut TextureAtlas)>,
+) {
+    query.iter_mut().for_each(|(mut animation, mut atlas)| {
+        let Some(clip) = animation_clips.get(&animation.set, &animation.clip) else {
+            return;
+        };
+        // Bypasses change detection, so only a switch of clips swaps the sprite sheet
+        let animation = animation.bypass_change_detection();
+        animation.elapsed += time.delta_seconds();
+        let frame_time = 1. / clip.fps;
+        while animation.elapsed >= frame_time {
+            animation.elapsed -= frame_time;
+            if animation.frame + 1 < clip.frames {
+                animation.frame += 1;
+            } else if clip.looping {
+                animation.frame = 0;
+            }
+        }
+        if atlas.index != animation.frame {
+            atlas.index = animation.frame;
+        }
+    });
+}
@@ -4,15 +4,12 @@ use std::{collections::HashMap, path::Path};
 use strum::IntoEnumIterator;
 use strum_macros::{AsRefStr, EnumIter};
 
-use crate::{player::PlayerMarker, states::AppState};
-
 /// The Plugin containing everything related to assets
 pub struct AssetPlugin;
 
 impl Plugin for AssetPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, init)
-            .add_systems(Update, animate_sprite.run_if(in_state(AppState::Playing)));
+        app.add_systems(Startup, init);
     }
 }
 
@@ -48,8 +45,34 @@ impl Material {
     pub fn is_small(&self) -> bool {
         SMALL_MATERIALS.contains(self)
     }
+
+    /// Returns the color representing the material where it is drawn without its texture, like on the minimap
+    pub fn color(&self) -> Color {
+        match self {
+            Material::GRASS_GREEN => Color::srgb_u8(98, 190, 60),
+            Material::GRASS_ORANGE => Color::srgb_u8(222, 140, 50),
+            Material::GRASS_PINK => Color::srgb_u8(230, 120, 180),
+            Material::WOOD => Color::srgb_u8(150, 95, 50),
+            Material::STEEL => Color::srgb_u8(150, 160, 170),
+            Material::BRONZE => Color::srgb_u8(190, 120, 70),
+            Material::GOLD => Color::srgb_u8(240, 200, 60),
+            Material::BRICK => Color::srgb_u8(170, 70, 60),
+        }
+    }
+
+    /// Returns whether the material has grass on top, making it suitable for the surface of terrain
+    pub fn is_grass(&self) -> bool {
+        GRASS_MATERIALS.contains(self)
+    }
 }
 
+/// A list defining what [Material]s have grass on top
+pub const GRASS_MATERIALS: [Material; 3] = [
+    Material::GRASS_GREEN,
+    Material::GRASS_ORANGE,
+    Material::GRASS_PINK,
+];
+
 /// A list defining what [Material]s have a 2x2 grid, instead of a 3x3 one
 pub const SMALL_MATERIALS: [Material; 4] = [
     Material::WOOD,
@@ -93,7 +116,6 @@ pub fn init(
     mut commands: Commands,
     mut materials: ResMut<Assets<ColorMaterial>>,
     asset_server: Res<AssetServer>,
-    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
 ) {
     let material_white = materials.add(ColorMaterial::from(Color::WHITE));
     let material_red = materials.add(ColorMaterial::from(Color::linear_rgb(150., 0., 0.)));
@@ -125,86 +147,23 @@ pub fn init(
         })
     });
     commands.insert_resource(image_handles);
-    let mut player_animations: PlayerAnimationMap = PlayerAnimationMap(HashMap::new());
-    PlayerAnimationState::iter().for_each(|animation| {
-        let columns = match animation {
-            PlayerAnimationState::FALL => 1,
-            PlayerAnimationState::IDLE => 11,
-            PlayerAnimationState::JUMP => 1,
-            PlayerAnimationState::RUN => 12,
-        };
-        let texture: Handle<Image> = asset_server.load(
-            Path::new("CHARACTER")
-                .join(animation.as_ref())
-                .with_extension("png"),
-        );
-        let layout = TextureAtlasLayout::from_grid(UVec2::splat(32), columns, 1, None, None);
-        let texture_atlas_layout = texture_atlas_layouts.add(layout);
-        let anim = PlayerAnimation {
-            texture,
-            texture_atlas_layout,
-        };
-        player_animations.0.insert(animation, anim);
-    });
-    commands.insert_resource(player_animations);
-    commands.insert_resource(PlayerAnimationState::IDLE);
-    commands.insert_resource(PlayerAnimationIndices { first: 0, last: 10 });
-    commands.insert_resource(PlayerAnimationTimer(Timer::from_seconds(
-        0.05,
-        TimerMode::Repeating,
-    )));
 }
 
 /// A Resource containing references to colored materials
 #[derive(Resource)]
 pub struct ColorResource(pub [(Handle<ColorMaterial>, String, Color); 2]);
 
-/// A map containing the players animation frames
-#[derive(Resource)]
-pub struct PlayerAnimationMap(pub HashMap<PlayerAnimationState, PlayerAnimation>);
-
-/// A struct that defines what is needed for a player animation
-pub struct PlayerAnimation {
-    pub texture_atlas_layout: Handle<TextureAtlasLayout>,
-    pub texture: Handle<Image>,
-}
-
-/// This enum describes all states the player could be in
-#[derive(
-    Resource, Hash, Ord, PartialOrd, PartialEq, Eq, Copy, Clone, EnumIter, AsRefStr, Debug,
-)]
+/// This enum describes all states the player could be in. Their names are the names of the player animation clips
+#[derive(Hash, Ord, PartialOrd, PartialEq, Eq, Copy, Clone, EnumIter, AsRefStr, Debug)]
+#[allow(non_camel_case_types)]
 pub enum PlayerAnimationState {
+    CROUCH,
+    CROUCH_SLIDE,
+    DASH,
     FALL,
     IDLE,
     JUMP,
+    LAND,
     RUN,
-}
-
-/// A Resource that keeps track of the current player animations size
-#[derive(Resource)]
-pub struct PlayerAnimationIndices {
-    pub first: usize,
-    pub last: usize,
-}
-
-/// A Resource that keeps track of whether the next animation frame should be rendered or not
-#[derive(Resource, Deref, DerefMut)]
-pub struct PlayerAnimationTimer(Timer);
-
-/// This System updates the player animations, if the [PlayerAnimationTimer] is expired
-fn animate_sprite(
-    time: Res<Time>,
-    mut timer: ResMut<PlayerAnimationTimer>,
-    indices: Res<PlayerAnimationIndices>,
-    mut query: Query<&mut TextureAtlas, With<PlayerMarker>>,
-) {
-    let mut atlas = query.single_mut();
-    timer.tick(time.delta());
-    if timer.just_finished() {
-        atlas.index = if atlas.index >= indices.last {
-            indices.first
-        } else {
-            atlas.index + 1
-        };
-    }
+    TURN,
 }
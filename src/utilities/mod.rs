@@ -1,5 +1,6 @@
 use bevy::prelude::*;
 
+pub mod animation;
 pub mod assets;
 pub mod easing;
 
@@ -8,6 +9,10 @@ pub struct UtilitiesPlugin;
 /// A Plugin containing utilities
 impl Plugin for UtilitiesPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins((easing::EasingPlugin, assets::AssetPlugin));
+        app.add_plugins((
+            easing::EasingPlugin,
+            assets::AssetPlugin,
+            animation::SpriteAnimationPlugin,
+        ));
     }
 }